@@ -0,0 +1,51 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+
+// The matrix types live in the example, so the bench reuses its source.
+#[allow(dead_code)]
+#[path = "../examples/matrix.rs"]
+mod matrix;
+
+use matrix::{Mat, Matrix};
+
+/// Sums the matrix by index, decoding the dimensions on every iteration.
+fn sum_decoding_each_time(m: &Mat<u64>) -> u64 {
+    let mut total = 0;
+    let mut i = 0;
+    while i < m.rows() {
+        let mut j = 0;
+        while j < m.cols() {
+            total += m[[i, j]];
+            j += 1;
+        }
+        i += 1;
+    }
+    total
+}
+
+/// Sums the matrix by index, decoding the dimensions once up front.
+fn sum_decoding_once(m: &Mat<u64>) -> u64 {
+    let [rows, cols] = m.dim();
+    let mut total = 0;
+    for i in 0..rows {
+        for j in 0..cols {
+            total += m[[i, j]];
+        }
+    }
+    total
+}
+
+fn indexing(c: &mut Criterion) {
+    let m: Matrix<u64> = Matrix::from_nested(vec![(0..256).collect(); 256]).unwrap();
+    let mut group = c.benchmark_group("index 256x256");
+    group.bench_function("decode each time", |b| {
+        b.iter(|| sum_decoding_each_time(black_box(&m)))
+    });
+    group.bench_function("decode once", |b| {
+        b.iter(|| sum_decoding_once(black_box(&m)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, indexing);
+criterion_main!(benches);
@@ -0,0 +1,116 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Error};
+
+/// Implements `fat_ptr::Meta` for a struct that is exactly the width of a `usize`.
+///
+/// The struct must be `#[repr(C)]` or `#[repr(transparent)]`, and `Copy`.
+/// Its fields must fill the whole `usize` with no padding between them,
+/// and each field type must implement `fat_ptr::NoPadding`, so that it has
+/// no padding inside it either. A struct that breaks these rules fails to build.
+#[proc_macro_derive(Meta)]
+pub fn derive_meta(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new_spanned(
+                name,
+                "`#[derive(Meta)]` can only be used on structs",
+            ))
+        }
+    };
+
+    let mut has_repr = false;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") || meta.path.is_ident("transparent") {
+                has_repr = true;
+            }
+            // Skip the arguments of `align(N)` and `packed(N)`.
+            if meta.input.peek(syn::token::Paren) {
+                let args;
+                syn::parenthesized!(args in meta.input);
+                args.parse::<proc_macro2::TokenStream>()?;
+            }
+            Ok(())
+        })?;
+    }
+    if !has_repr {
+        return Err(Error::new_spanned(
+            name,
+            "`#[derive(Meta)]` requires `#[repr(C)]` or `#[repr(transparent)]`",
+        ));
+    }
+
+    let message = format!(
+        "`#[derive(Meta)]` requires `{name}` to be exactly the size of a `usize`, with no padding"
+    );
+    let field_tys: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    // Padding bytes are uninitialized, so it is not enough for the struct
+    // to be the right size: its fields must cover every byte.
+    let size_check = |ty: proc_macro2::TokenStream| {
+        quote! {
+            ::core::assert!(
+                ::core::mem::size_of::<#ty>() == ::core::mem::size_of::<usize>()
+                    && 0 #(+ ::core::mem::size_of::<#field_tys>())* == ::core::mem::size_of::<usize>(),
+                #message
+            )
+        }
+    };
+
+    // Generic structs can only be checked once their parameters are known,
+    // so for them the check goes in each method instead.
+    let (item_check, method_check) = if input.generics.params.is_empty() {
+        let check = size_check(quote!(#name));
+        (Some(quote!(const _: () = #check;)), None)
+    } else {
+        let check = size_check(quote!(Self));
+        (None, Some(quote!(const { #check };)))
+    };
+
+    let mut generics = input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    where_clause
+        .predicates
+        .push(parse_quote!(Self: ::core::marker::Copy));
+    // The size check only rules out padding between the fields,
+    // so each field has to promise that it has none inside it.
+    for ty in &field_tys {
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: ::fat_ptr::NoPadding));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        #item_check
+
+        impl #impl_generics ::fat_ptr::Meta for #name #ty_generics #where_clause {
+            #[inline(always)]
+            fn into_bytes(self) -> usize {
+                #method_check
+                // SAFETY: `Self` is exactly as wide as a usize with no padding,
+                // so every byte is initialized, and any bit pattern for usize is valid.
+                unsafe { ::core::mem::transmute_copy::<Self, usize>(&self) }
+            }
+            #[inline(always)]
+            unsafe fn from_bytes(val: usize) -> Self {
+                #method_check
+                // SAFETY: `Self` is exactly as wide as a usize, and the caller
+                // guarantees that `val` came from `into_bytes`, so it is a valid
+                // bit pattern for `Self`.
+                ::core::mem::transmute_copy::<usize, Self>(&val)
+            }
+        }
+    })
+}
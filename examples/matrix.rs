@@ -1,4 +1,5 @@
-use fat_ptr::{Fat, Meta};
+use fat_ptr::{ElemCount, Fat, FatBox, NoPadding};
+use rayon::iter::ParallelIterator;
 
 /// An integer half the width of a `usize`
 #[derive(Clone, Copy)]
@@ -10,6 +11,9 @@ struct Halfsize(
     #[cfg(target_pointer_width = "16")] u8,
 );
 
+// SAFETY: Halfsize is a transparent wrapper around an integer.
+unsafe impl NoPadding for Halfsize {}
+
 impl From<Halfsize> for usize {
     fn from(val: Halfsize) -> usize {
         match val.0.try_into() {
@@ -45,26 +49,50 @@ impl std::ops::Mul for Halfsize {
 }
 
 /// Holds two values of the same type, with a guaranteed memory layout.
+#[derive(Clone, Copy, PartialEq, fat_ptr_derive::Meta)]
 #[repr(C)]
 struct Pair<T>(T, T);
 
-impl Meta for Pair<Halfsize> {
-    #[inline(always)]
-    fn into_bytes(self) -> usize {
-        // SAFETY: Transmuting Pair<Halfsize> -> usize is sound, as Halfsize is
-        // exactly half the width of a usize, Pair is repr(C), and any
-        // bit pattern for usize is valid.
-        unsafe { std::mem::transmute(self) }
-    }
-    #[inline(always)]
-    unsafe fn from_bytes(val: usize) -> Self {
-        // SAFETY: Transmuting usize -> Pair<Halfsize> is sound, as Halfsize is
-        // exactly half the width of a usize, Pair is repr(C), and any
-        // bit pattern for Halfsize is valid.
-        std::mem::transmute(val)
+// SAFETY: The count only depends on the dimensions, which round-trip exactly.
+unsafe impl ElemCount for Pair<Halfsize> {
+    fn elem_count(&self) -> usize {
+        self.0 * self.1
     }
 }
 
+/// A floating-point number.
+pub trait Float:
+    Copy
+    + PartialOrd
+    + std::ops::Add<Output = Self>
+    + std::ops::Sub<Output = Self>
+    + std::ops::Mul<Output = Self>
+    + std::ops::Div<Output = Self>
+{
+    const ZERO: Self;
+    fn abs(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn from_usize(n: usize) -> Self;
+}
+
+macro_rules! impl_float {
+    ($($t:ty),*) => {$(
+        impl Float for $t {
+            const ZERO: Self = 0.0;
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+            fn sqrt(self) -> Self {
+                <$t>::sqrt(self)
+            }
+            fn from_usize(n: usize) -> Self {
+                n as $t
+            }
+        }
+    )*};
+}
+impl_float!(f32, f64);
+
 /// An owned matrix.
 pub struct Matrix<T> {
     items: Vec<T>,
@@ -87,12 +115,112 @@ impl<T> Matrix<T> {
         }
     }
 
+    /// Creates an `n×1` matrix using the buffer of `data` directly.
+    pub fn column_vec(data: Vec<T>) -> Self {
+        let rows = data.len();
+        Self {
+            items: data,
+            rows: rows.try_into().expect("`data` must fit in half a usize"),
+            cols: 1.try_into().unwrap(),
+        }
+    }
+    /// Creates a `1×n` matrix using the buffer of `data` directly.
+    pub fn row_vec(data: Vec<T>) -> Self {
+        let cols = data.len();
+        Self {
+            items: data,
+            rows: 1.try_into().unwrap(),
+            cols: cols.try_into().expect("`data` must fit in half a usize"),
+        }
+    }
+
     pub fn rows(&self) -> usize {
         self.rows.into()
     }
     pub fn cols(&self) -> usize {
         self.cols.into()
     }
+    /// Builds a `rows×cols` matrix from coordinate/value pairs,
+    /// with every other element set to its default value.
+    /// Later entries overwrite earlier ones at the same coordinates.
+    pub fn from_sparse(
+        rows: usize,
+        cols: usize,
+        entries: &[([usize; 2], T)],
+    ) -> Result<Self, ShapeError>
+    where
+        T: Clone + Default,
+    {
+        if Halfsize::try_from(rows).is_err() || Halfsize::try_from(cols).is_err() {
+            return Err(ShapeError);
+        }
+        let mut matrix = Self::new(rows, cols);
+        for ([i, j], x) in entries {
+            if *i >= rows || *j >= cols {
+                return Err(ShapeError);
+            }
+            matrix[[*i, *j]] = x.clone();
+        }
+        Ok(matrix)
+    }
+
+    /// Builds a matrix from a vector of rows, which must all have the same length.
+    pub fn from_nested(rows: Vec<Vec<T>>) -> Result<Self, ShapeError> {
+        let cols = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != cols) {
+            return Err(ShapeError);
+        }
+        Ok(Self {
+            rows: rows.len().try_into().map_err(|_| ShapeError)?,
+            cols: cols.try_into().map_err(|_| ShapeError)?,
+            items: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Gets the elements of this matrix in row-major order, discarding its dimensions.
+    pub fn into_vec(self) -> Vec<T> {
+        self.items
+    }
+}
+
+impl Matrix<f64> {
+    /// Parses a matrix with one row per line and entries separated by commas.
+    /// Blank lines are skipped.
+    pub fn from_rows_str(s: &str) -> Result<Self, ParseError> {
+        let mut items = Vec::new();
+        let mut rows = 0;
+        let mut cols = None;
+        for (i, line) in s.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let before = items.len();
+            for (j, entry) in line.split(',').enumerate() {
+                let x = entry.trim().parse().map_err(|source| ParseError::Number {
+                    line: i + 1,
+                    column: j + 1,
+                    source,
+                })?;
+                items.push(x);
+            }
+            let len = items.len() - before;
+            if *cols.get_or_insert(len) != len {
+                return Err(ParseError::Ragged { line: i + 1 });
+            }
+            rows += 1;
+        }
+
+        let (rows, cols) = Self::parsed_dim(rows, cols.unwrap_or(0))?;
+        Ok(Self { items, rows, cols })
+    }
+    /// Packs the dimensions of a parsed matrix, if they each fit in half a usize.
+    fn parsed_dim(rows: usize, cols: usize) -> Result<(Halfsize, Halfsize), ParseError> {
+        let too_large = |_| ParseError::TooLarge;
+        Ok((
+            rows.try_into().map_err(too_large)?,
+            cols.try_into().map_err(too_large)?,
+        ))
+    }
 }
 
 /// A reference to a matrix.
@@ -109,9 +237,616 @@ impl<T> Mat<T> {
         let Pair(_, cols) = self.0.meta();
         cols.into()
     }
-    fn dim(&self) -> Pair<usize> {
+    /// Gets the number of rows and columns, decoding the metadata once.
+    /// In a hot loop, call this once up front rather than calling `rows` and `cols` each time.
+    #[inline]
+    pub fn dim(&self) -> [usize; 2] {
+        let Pair(rows, cols) = self.0.meta();
+        [rows.into(), cols.into()]
+    }
+
+    /// Gets the elements of this matrix, in row-major order.
+    pub fn as_slice(&self) -> &[T] {
+        self.0.as_slice()
+    }
+    /// Gets the elements of this matrix mutably, in row-major order.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.0.as_mut_slice()
+    }
+    fn row(&self, i: usize) -> &[T] {
+        let cols = self.cols();
+        &self.as_slice()[i * cols..(i + 1) * cols]
+    }
+    fn row_mut(&mut self, i: usize) -> &mut [T] {
+        let cols = self.cols();
+        &mut self.as_mut_slice()[i * cols..(i + 1) * cols]
+    }
+
+    /// Cyclically shifts the rows of this matrix, so that row `i` moves to `(i + by) % rows`.
+    pub fn rotate_rows(&mut self, by: usize) {
+        let [rows, cols] = self.dim();
+        if rows == 0 {
+            return;
+        }
+        // Rows are stored contiguously, so rotating the rows
+        // is the same as rotating the flat buffer by whole rows.
+        self.as_mut_slice().rotate_right(by % rows * cols);
+    }
+
+    /// Reorders the rows of this matrix, so that row `i` comes from row `perm[i]`.
+    /// # Panics
+    /// If `perm` is not a permutation of `0..rows`.
+    pub fn permute_rows(&mut self, perm: &[usize]) {
+        let [rows, cols] = self.dim();
+        assert_eq!(perm.len(), rows, "`perm` must have one entry per row");
+        let mut seen = vec![false; rows];
+        for &p in perm {
+            assert!(
+                p < rows && !seen[p],
+                "`perm` must be a permutation of the rows"
+            );
+            seen[p] = true;
+        }
+
+        // Follow each cycle of the permutation, swapping every row into place.
+        let mut done = vec![false; rows];
+        let items = self.as_mut_slice();
+        for start in 0..rows {
+            let mut i = start;
+            while !done[i] {
+                done[i] = true;
+                let from = perm[i];
+                if from == start {
+                    break;
+                }
+                let (a, b) = items.split_at_mut(i.max(from) * cols);
+                a[i.min(from) * cols..][..cols].swap_with_slice(&mut b[..cols]);
+                i = from;
+            }
+        }
+    }
+
+    /// Returns a wrapper that prints this matrix with one row per line,
+    /// and its columns aligned.
+    pub fn display(&self) -> MatDisplay<'_, T> {
+        MatDisplay(self)
+    }
+
+    /// Adds `row` to every row of this matrix, in place.
+    /// # Panics
+    /// If the length of `row` is not the number of columns.
+    pub fn add_row_vector(&mut self, row: &[T])
+    where
+        T: Copy + std::ops::AddAssign,
+    {
+        let cols = self.cols();
+        assert_eq!(row.len(), cols, "row vector must have one item per column");
+        for (k, x) in self.as_mut_slice().iter_mut().enumerate() {
+            *x += row[k % cols];
+        }
+    }
+    /// Adds `col` to every column of this matrix, in place.
+    /// # Panics
+    /// If the length of `col` is not the number of rows.
+    pub fn add_col_vector(&mut self, col: &[T])
+    where
+        T: Copy + std::ops::AddAssign,
+    {
+        let [rows, cols] = self.dim();
+        assert_eq!(col.len(), rows, "column vector must have one item per row");
+        for (k, x) in self.as_mut_slice().iter_mut().enumerate() {
+            *x += col[k / cols];
+        }
+    }
+
+    /// Sets every element on the main diagonal to `value`,
+    /// leaving the other elements untouched.
+    pub fn fill_diagonal(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        let [rows, cols] = self.dim();
+        for i in 0..rows.min(cols) {
+            self[[i, i]] = value.clone();
+        }
+    }
+
+    /// Checks if this matrix has the same dimensions as `other`,
+    /// and every element is within `tol` of the corresponding element of `other`.
+    pub fn approx_eq(&self, other: &Mat<T>, tol: T) -> bool
+    where
+        T: Float,
+    {
+        self.dim() == other.dim()
+            && self
+                .as_slice()
+                .iter()
+                .zip(other.as_slice())
+                .all(|(&a, &b)| (a - b).abs() <= tol)
+    }
+
+    /// Divides each row of this matrix by its L2 norm, in place.
+    /// Rows with a norm of zero are left as they are.
+    pub fn normalize_rows(&mut self)
+    where
+        T: Float,
+    {
+        for i in 0..self.rows() {
+            let row = self.row_mut(i);
+            let norm = row.iter().fold(T::ZERO, |acc, &x| acc + x * x).sqrt();
+            if norm == T::ZERO {
+                continue;
+            }
+            for x in row {
+                *x = *x / norm;
+            }
+        }
+    }
+
+    /// Gets the Frobenius norm of this matrix,
+    /// which is the square root of the sum of its squared elements.
+    pub fn frobenius_norm(&self) -> T
+    where
+        T: Float,
+    {
+        self.as_slice()
+            .iter()
+            .fold(T::ZERO, |acc, &x| acc + x * x)
+            .sqrt()
+    }
+
+    /// Clamps every element of this matrix into `[min, max]`, in place.
+    /// # Panics
+    /// If `min > max`.
+    pub fn clamp(&mut self, min: T, max: T)
+    where
+        T: PartialOrd + Copy,
+    {
+        assert!(min <= max, "`min` must not be greater than `max`");
+        for x in self.as_mut_slice() {
+            if *x < min {
+                *x = min;
+            } else if *x > max {
+                *x = max;
+            }
+        }
+    }
+
+    /// Calls `f` with the coordinates of and a mutable reference to
+    /// every element of this matrix, in row-major order.
+    pub fn for_each_mut(&mut self, mut f: impl FnMut([usize; 2], &mut T)) {
+        let cols = self.cols();
+        for (k, x) in self.as_mut_slice().iter_mut().enumerate() {
+            f([k / cols, k % cols], x);
+        }
+    }
+
+    /// Sums every element of this matrix.
+    pub fn sum(&self) -> T
+    where
+        T: Copy + std::iter::Sum,
+    {
+        self.as_slice().iter().copied().sum()
+    }
+    /// Gets the average of every element of this matrix.
+    /// # Panics
+    /// If the matrix is empty.
+    pub fn mean(&self) -> T
+    where
+        T: Float + std::iter::Sum,
+    {
+        let count = self.as_slice().len();
+        assert!(count != 0, "cannot take the mean of an empty matrix");
+        self.sum() / T::from_usize(count)
+    }
+
+    /// Gets the coordinates of the largest element of this matrix,
+    /// or `None` if it is empty. Ties go to the first element in row-major order.
+    pub fn argmax(&self) -> Option<[usize; 2]>
+    where
+        T: PartialOrd,
+    {
+        let mut best: Option<(usize, &T)> = None;
+        for (k, x) in self.as_slice().iter().enumerate() {
+            if best.is_none_or(|(_, max)| x > max) {
+                best = Some((k, x));
+            }
+        }
+        let cols = self.cols();
+        best.map(|(k, _)| [k / cols, k % cols])
+    }
+
+    /// Multiplies this matrix by the column vector `v`.
+    fn mul_vec(&self, v: &[T]) -> Vec<T>
+    where
+        T: Float,
+    {
+        (0..self.rows())
+            .map(|i| {
+                let row = self.row(i);
+                row.iter().zip(v).fold(T::ZERO, |acc, (&a, &b)| acc + a * b)
+            })
+            .collect()
+    }
+
+    /// Estimates the eigenvalue of largest magnitude and its eigenvector,
+    /// using `iters` rounds of power iteration. The eigenvector has unit length.
+    /// # Panics
+    /// If the matrix is not square.
+    pub fn dominant_eigenpair(&self, iters: usize) -> (T, Vec<T>)
+    where
+        T: Float,
+    {
+        let [rows, cols] = self.dim();
+        assert_eq!(rows, cols, "power iteration needs a square matrix");
+
+        let norm = |v: &[T]| v.iter().fold(T::ZERO, |acc, &x| acc + x * x).sqrt();
+
+        let start = T::from_usize(1) / T::from_usize(rows.max(1)).sqrt();
+        let mut v = vec![start; rows];
+        for _ in 0..iters {
+            let w = self.mul_vec(&v);
+            let len = norm(&w);
+            if len == T::ZERO {
+                break;
+            }
+            v = w.into_iter().map(|x| x / len).collect();
+        }
+
+        // Since `v` has unit length, the Rayleigh quotient is just `v · Av`.
+        let av = self.mul_vec(&v);
+        let value = v.iter().zip(&av).fold(T::ZERO, |acc, (&a, &b)| acc + a * b);
+        (value, v)
+    }
+
+    /// Iterates over a diagonal of this matrix.
+    /// An `offset` of 0 is the main diagonal, positive offsets are above it,
+    /// and negative offsets are below it.
+    pub fn diagonal(&self, offset: isize) -> impl Iterator<Item = &T> {
+        let [rows, cols] = self.dim();
+        let (i0, j0) = if offset >= 0 {
+            (0, offset.unsigned_abs())
+        } else {
+            (offset.unsigned_abs(), 0)
+        };
+        let len = rows.saturating_sub(i0).min(cols.saturating_sub(j0));
+        (0..len).map(move |k| &self[[i0 + k, j0 + k]])
+    }
+
+    /// Creates an identity matrix with the same dimensions as this one.
+    /// # Panics
+    /// If the matrix is not square.
+    pub fn identity_like(&self) -> Matrix<T>
+    where
+        T: From<u8>,
+    {
         let Pair(rows, cols) = self.0.meta();
-        Pair(rows.into(), cols.into())
+        let n = usize::from(rows);
+        assert_eq!(n, usize::from(cols), "identity matrices must be square");
+
+        let items = (0..n * n)
+            .map(|k| T::from(u8::from(k / n == k % n)))
+            .collect();
+        Matrix { items, rows, cols }
+    }
+
+    /// Collects the coordinates and values of every element that isn't the default value.
+    pub fn to_sparse(&self) -> Vec<([usize; 2], T)>
+    where
+        T: Clone + PartialEq + Default,
+    {
+        let cols = self.cols();
+        let zero = T::default();
+        self.as_slice()
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| **x != zero)
+            .map(|(k, x)| ([k / cols, k % cols], x.clone()))
+            .collect()
+    }
+
+    /// Copies the rows of this matrix into separate vectors.
+    pub fn to_nested(&self) -> Vec<Vec<T>>
+    where
+        T: Clone,
+    {
+        (0..self.rows()).map(|i| self.row(i).to_vec()).collect()
+    }
+
+    /// Copies this matrix with row `skip_row` and column `skip_col` removed.
+    pub fn minor(&self, skip_row: usize, skip_col: usize) -> Matrix<T>
+    where
+        T: Clone,
+    {
+        let [rows, cols] = self.dim();
+        assert!(skip_row < rows);
+        assert!(skip_col < cols);
+
+        let items: Vec<T> = (0..rows)
+            .filter(|&i| i != skip_row)
+            .flat_map(|i| {
+                let row = self.row(i);
+                row[..skip_col].iter().chain(&row[skip_col + 1..])
+            })
+            .cloned()
+            .collect();
+
+        Matrix {
+            items,
+            rows: (rows - 1).try_into().unwrap(),
+            cols: (cols - 1).try_into().unwrap(),
+        }
+    }
+
+    /// Copies this matrix reflected across its anti-diagonal,
+    /// so that element `[i, j]` moves to `[cols - 1 - j, rows - 1 - i]`.
+    pub fn anti_transpose(&self) -> Matrix<T>
+    where
+        T: Clone,
+    {
+        let [rows, cols] = self.dim();
+        let items: Vec<T> = (0..cols)
+            .flat_map(|i| (0..rows).map(move |j| (rows - 1 - j, cols - 1 - i)))
+            .map(|(i, j)| self.row(i)[j].clone())
+            .collect();
+
+        let Pair(rows, cols) = self.0.meta();
+        Matrix {
+            items,
+            rows: cols,
+            cols: rows,
+        }
+    }
+
+    /// Repeats this matrix in a grid, `vert` times down and `horiz` times across.
+    pub fn tile(&self, vert: usize, horiz: usize) -> Matrix<T>
+    where
+        T: Clone,
+    {
+        let [rows, cols] = self.dim();
+        let (new_rows, new_cols) = (rows * vert, cols * horiz);
+        let items: Vec<T> = (0..new_rows)
+            .flat_map(|i| (0..new_cols).map(move |j| (i % rows, j % cols)))
+            .map(|(i, j)| self.row(i)[j].clone())
+            .collect();
+
+        Matrix {
+            items,
+            rows: new_rows
+                .try_into()
+                .expect("tiled rows must fit in half a usize"),
+            cols: new_cols
+                .try_into()
+                .expect("tiled columns must fit in half a usize"),
+        }
+    }
+
+    /// Iterates over views of `block` rows at a time, from the top.
+    /// The last view may have fewer rows.
+    /// # Panics
+    /// If `block` is zero.
+    pub fn row_blocks(&self, block: usize) -> impl Iterator<Item = &Mat<T>> {
+        assert!(block != 0, "`block` must not be zero");
+        let [rows, cols] = self.dim();
+        let Pair(_, meta_cols) = self.0.meta();
+        (0..rows).step_by(block).map(move |start| {
+            let n = block.min(rows - start);
+            let data = &self.as_slice()[start * cols..(start + n) * cols];
+            let fat = Fat::from_slice(data, Pair(n.try_into().unwrap(), meta_cols));
+            // SAFETY: `Mat` is repr(transparent), so its sound to transmute
+            // from &Fat -> &Mat
+            unsafe { std::mem::transmute::<&Fat<T, Pair<Halfsize>>, &Mat<T>>(fat) }
+        })
+    }
+
+    /// Convolves this matrix with `kernel`, keeping only the outputs where
+    /// the kernel lies entirely inside the matrix.
+    /// The result has `rows - kernel.rows() + 1` rows and `cols - kernel.cols() + 1` columns.
+    /// # Panics
+    /// If the kernel is empty, or larger than this matrix in either dimension.
+    pub fn convolve(&self, kernel: &Mat<T>) -> Matrix<T>
+    where
+        T: Copy + std::ops::Mul<Output = T> + std::iter::Sum,
+    {
+        let [rows, cols] = self.dim();
+        let [k_rows, k_cols] = kernel.dim();
+        assert!(k_rows != 0 && k_cols != 0, "`kernel` must not be empty");
+        assert!(
+            k_rows <= rows && k_cols <= cols,
+            "`kernel` must fit within the matrix"
+        );
+        let (out_rows, out_cols) = (rows - k_rows + 1, cols - k_cols + 1);
+
+        // Convolution flips the kernel, so the last kernel element
+        // lines up with the top left of each window.
+        let items: Vec<T> = (0..out_rows)
+            .flat_map(|i| (0..out_cols).map(move |j| (i, j)))
+            .map(|(i, j)| {
+                (0..k_rows)
+                    .flat_map(|a| (0..k_cols).map(move |b| (a, b)))
+                    .map(|(a, b)| {
+                        self.row(i + a)[j + b] * kernel.row(k_rows - 1 - a)[k_cols - 1 - b]
+                    })
+                    .sum()
+            })
+            .collect();
+
+        Matrix {
+            items,
+            rows: out_rows.try_into().unwrap(),
+            cols: out_cols.try_into().unwrap(),
+        }
+    }
+
+    /// Copies this matrix into the middle of a larger one,
+    /// with the given number of rows or columns of `fill` on each side.
+    pub fn pad(&self, top: usize, bottom: usize, left: usize, right: usize, fill: T) -> Matrix<T>
+    where
+        T: Clone,
+    {
+        let [rows, cols] = self.dim();
+        let (new_rows, new_cols) = (top + rows + bottom, left + cols + right);
+        let mut items = vec![fill.clone(); top * new_cols];
+        for i in 0..rows {
+            items.extend(std::iter::repeat_n(fill.clone(), left));
+            items.extend_from_slice(self.row(i));
+            items.extend(std::iter::repeat_n(fill.clone(), right));
+        }
+        items.extend(std::iter::repeat_n(fill, bottom * new_cols));
+
+        Matrix {
+            items,
+            rows: new_rows
+                .try_into()
+                .expect("padded rows must fit in half a usize"),
+            cols: new_cols
+                .try_into()
+                .expect("padded columns must fit in half a usize"),
+        }
+    }
+
+    /// Divides this matrix into two mutable views at row `at`.
+    /// # Panics
+    /// If `at > rows`.
+    pub fn split_rows_mut(&mut self, at: usize) -> (&mut Mat<T>, &mut Mat<T>) {
+        let [rows, cols] = self.dim();
+        assert!(
+            at <= rows,
+            "`at` must not be greater than the number of rows"
+        );
+        let Pair(_, meta_cols) = self.0.meta();
+        // Rows are stored contiguously, so the split is at a whole number of rows.
+        let (top, bottom) = self.as_mut_slice().split_at_mut(at * cols);
+        let top = Fat::from_slice_mut(top, Pair(at.try_into().unwrap(), meta_cols));
+        let bottom = Fat::from_slice_mut(bottom, Pair((rows - at).try_into().unwrap(), meta_cols));
+        // SAFETY: `Mat` is repr(transparent), so its sound to transmute
+        // from &mut Fat -> &mut Mat
+        unsafe {
+            (
+                std::mem::transmute::<&mut Fat<T, Pair<Halfsize>>, &mut Mat<T>>(top),
+                std::mem::transmute::<&mut Fat<T, Pair<Halfsize>>, &mut Mat<T>>(bottom),
+            )
+        }
+    }
+
+    /// Copies the elements of this matrix into a flat fat box, in row-major order.
+    pub fn to_flat_box(&self) -> FatBox<T, usize>
+    where
+        T: Clone,
+    {
+        let items = self.as_slice().to_vec();
+        let count = items.len();
+        FatBox::from_vec(items, count)
+    }
+
+    /// Folds over each row of this matrix, in order.
+    pub fn fold_rows<B>(&self, init: B, f: impl FnMut(B, &[T]) -> B) -> B {
+        (0..self.rows()).map(|i| self.row(i)).fold(init, f)
+    }
+
+    /// Sums each row of this matrix.
+    pub fn row_sums(&self) -> Vec<T>
+    where
+        T: Copy + std::iter::Sum,
+    {
+        (0..self.rows())
+            .map(|i| self.row(i).iter().copied().sum())
+            .collect()
+    }
+    /// Sums each column of this matrix.
+    pub fn column_sums(&self) -> Vec<T>
+    where
+        T: Copy + std::iter::Sum,
+    {
+        let [rows, cols] = self.dim();
+        (0..cols)
+            .map(|j| (0..rows).map(|i| self[[i, j]]).sum())
+            .collect()
+    }
+
+    /// Combines this matrix with another of the same dimensions, element by element.
+    pub fn zip_with<U, R>(
+        &self,
+        other: &Mat<U>,
+        mut f: impl FnMut(&T, &U) -> R,
+    ) -> Result<Matrix<R>, ShapeError> {
+        let Pair(rows, cols) = self.0.meta();
+        if self.dim() != other.dim() {
+            return Err(ShapeError);
+        }
+        let items: Vec<R> = self
+            .as_slice()
+            .iter()
+            .zip(other.as_slice())
+            .map(|(a, b)| f(a, b))
+            .collect();
+
+        Ok(Matrix { items, rows, cols })
+    }
+
+    /// Builds a new matrix by applying `f` to each row of this one.
+    /// Every call to `f` must return a row of the same length, which
+    /// becomes the number of columns of the result.
+    pub fn map_rows<U>(&self, mut f: impl FnMut(&[T]) -> Vec<U>) -> Result<Matrix<U>, ShapeError> {
+        let rows = self.rows();
+        let mut items = Vec::new();
+        let mut cols = None;
+        for i in 0..rows {
+            let row = f(self.row(i));
+            if *cols.get_or_insert(row.len()) != row.len() {
+                return Err(ShapeError);
+            }
+            items.extend(row);
+        }
+
+        Ok(Matrix {
+            items,
+            rows: rows.try_into().map_err(|_| ShapeError)?,
+            cols: cols.unwrap_or(0).try_into().map_err(|_| ShapeError)?,
+        })
+    }
+}
+
+impl<T: Send> Mat<T> {
+    /// Iterates over the rows of this matrix in parallel, as disjoint mutable slices.
+    /// A matrix with no columns yields no rows.
+    pub fn par_rows_mut(&mut self) -> impl ParallelIterator<Item = &mut [T]> {
+        use rayon::slice::ParallelSliceMut;
+        let cols = self.cols().max(1);
+        self.as_mut_slice().par_chunks_mut(cols)
+    }
+}
+
+/// Prints a matrix in aligned rows. Returned by [`Mat::display`].
+pub struct MatDisplay<'a, T>(&'a Mat<T>);
+
+impl<T: std::fmt::Display> std::fmt::Display for MatDisplay<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [rows, cols] = self.0.dim();
+        let cells: Vec<String> = self.0.as_slice().iter().map(|x| x.to_string()).collect();
+
+        // Each column is as wide as its widest cell.
+        let widths: Vec<usize> = (0..cols)
+            .map(|j| {
+                (0..rows)
+                    .map(|i| cells[i * cols + j].len())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        for i in 0..rows {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for (j, width) in widths.iter().enumerate() {
+                if j > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{:>width$}", cells[i * cols + j])?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -134,9 +869,54 @@ impl<T> std::ops::DerefMut for Matrix<T> {
     }
 }
 
+/// Collects the items into a column vector (`n×1`).
+/// Use [`Matrix::row_vec`] for a row vector.
+impl<T> FromIterator<T> for Matrix<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Matrix::column_vec(iter.into_iter().collect())
+    }
+}
+
+impl<T> std::ops::MulAssign<T> for Matrix<T>
+where
+    T: Copy + std::ops::MulAssign,
+{
+    fn mul_assign(&mut self, rhs: T) {
+        for x in &mut self.items {
+            *x *= rhs;
+        }
+    }
+}
+
+/// Implements an element-wise operator for references to matrices.
+/// Panics if the dimensions don't match.
+/// For `Div` and `Rem`, dividing by zero does whatever the operator does for `T`:
+/// integers panic, and floats produce infinity or NaN.
+macro_rules! impl_elementwise_op {
+    ($($op:ident, $method:ident;)*) => {$(
+        impl<T> std::ops::$op for &Matrix<T>
+        where
+            T: Copy + std::ops::$op<Output = T>,
+        {
+            type Output = Matrix<T>;
+            fn $method(self, rhs: Self) -> Matrix<T> {
+                self.zip_with(rhs, |&a, &b| std::ops::$op::$method(a, b))
+                    .expect("matrices must have the same dimensions")
+            }
+        }
+    )*};
+}
+impl_elementwise_op! {
+    BitAnd, bitand;
+    BitOr, bitor;
+    BitXor, bitxor;
+    Div, div;
+    Rem, rem;
+}
+
 impl<T> std::borrow::Borrow<Mat<T>> for Matrix<T> {
     fn borrow(&self) -> &Mat<T> {
-        &*self
+        self
     }
 }
 
@@ -146,16 +926,9 @@ where
 {
     type Owned = Matrix<T>;
     fn to_owned(&self) -> Matrix<T> {
-        // fetch the first field of the fat pointer.
-        let ptr = self.0.ptr();
         // fetch the second field of the fat pointer.
         let Pair(rows, cols) = self.0.meta();
-
-        // SAFETY: A Mat can only be safely created from the `Deref` or
-        // `DerefMut` impls, so we know that `ptr` must point to `rows * cols`
-        // properly initialized values of T.
-        let items = unsafe { std::slice::from_raw_parts(ptr, rows * cols) };
-        let items = items.to_vec();
+        let items = self.as_slice().to_vec();
 
         Matrix { items, rows, cols }
     }
@@ -164,41 +937,184 @@ where
 impl<T> std::ops::Index<[usize; 2]> for Mat<T> {
     type Output = T;
     fn index(&self, [i, j]: [usize; 2]) -> &T {
-        let Pair(rows, cols) = self.dim();
+        let [rows, cols] = self.dim();
         assert!(i < rows);
         assert!(j < cols);
 
-        // SAFETY: A `&Mat` can only be safely created from the `Deref` or
-        // `DerefMut` impls, so we know that `ptr` must point to `rows * cols`
-        // properly initialized values of T.
-        // Since `i` < `rows` and `j` < `cols`, we know that `i * cols + j`
-        // must be less than `rows * cols`.
-        unsafe {
-            let ptr = self.0.ptr();
-            &*ptr.add(i * cols + j)
-        }
+        &self.as_slice()[i * cols + j]
     }
 }
 impl<T> std::ops::IndexMut<[usize; 2]> for Mat<T> {
     fn index_mut(&mut self, [i, j]: [usize; 2]) -> &mut T {
-        let Pair(rows, cols) = self.dim();
+        let [rows, cols] = self.dim();
         assert!(i < rows);
         assert!(j < cols);
 
-        // SAFETY: A `&mut Mat` can only be safely created from the `DerefMut`
-        // impl, so we know that `ptr` must point to `rows * cols` properly
-        // initialized values of T.
-        // Since `i` < `rows` and `j` < `cols`, we know that `i * cols + j`
-        // must be less than `rows * cols`.
-        unsafe {
-            let ptr = self.0.mut_ptr() as *mut T;
-            &mut *ptr.add(i * cols + j)
+        &mut self.as_mut_slice()[i * cols + j]
+    }
+}
+
+/// An error returned when the dimensions of matrices do not line up.
+#[derive(Debug)]
+pub struct ShapeError;
+
+impl std::fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("mismatched matrix dimensions")
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+/// An error returned when a matrix can't be parsed from text.
+/// Lines and columns are counted from 1.
+#[derive(Debug)]
+pub enum ParseError {
+    /// A row has a different number of entries than the first row.
+    Ragged { line: usize },
+    /// An entry is not a number.
+    Number {
+        line: usize,
+        column: usize,
+        source: std::num::ParseFloatError,
+    },
+    /// There are too many rows or columns to fit in half a usize.
+    TooLarge,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ragged { line } => write!(f, "row on line {line} has the wrong length"),
+            Self::Number { line, column, .. } => {
+                write!(f, "invalid number on line {line}, column {column}")
+            }
+            Self::TooLarge => f.write_str("too many rows or columns"),
         }
     }
 }
 
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Ragged { .. } | Self::TooLarge => None,
+            Self::Number { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Stacks matrices on top of each other.
+/// Every matrix must have the same number of columns.
+pub fn vstack<T: Clone>(mats: &[&Mat<T>]) -> Result<Matrix<T>, ShapeError> {
+    let cols = mats.first().map_or(0, |m| m.cols());
+    if mats.iter().any(|m| m.cols() != cols) {
+        return Err(ShapeError);
+    }
+    let rows = mats.iter().map(|m| m.rows()).sum::<usize>();
+
+    // Rows are contiguous, so stacking vertically is just concatenation.
+    let items: Vec<T> = mats.iter().flat_map(|m| m.as_slice()).cloned().collect();
+
+    Ok(Matrix {
+        items,
+        rows: rows.try_into().map_err(|_| ShapeError)?,
+        cols: cols.try_into().map_err(|_| ShapeError)?,
+    })
+}
+
+/// Stacks matrices side by side.
+/// Every matrix must have the same number of rows.
+pub fn hstack<T: Clone>(mats: &[&Mat<T>]) -> Result<Matrix<T>, ShapeError> {
+    let rows = mats.first().map_or(0, |m| m.rows());
+    if mats.iter().any(|m| m.rows() != rows) {
+        return Err(ShapeError);
+    }
+    let cols = mats.iter().map(|m| m.cols()).sum::<usize>();
+
+    // Each row of the result is made of the same row from every matrix.
+    let items: Vec<T> = (0..rows)
+        .flat_map(|i| mats.iter().flat_map(move |m| m.row(i)))
+        .cloned()
+        .collect();
+
+    Ok(Matrix {
+        items,
+        rows: rows.try_into().map_err(|_| ShapeError)?,
+        cols: cols.try_into().map_err(|_| ShapeError)?,
+    })
+}
+
+/// Builds a block-diagonal matrix, with each matrix placed along the diagonal
+/// and every other element set to its default value.
+pub fn block_diag<T: Clone + Default>(mats: &[&Mat<T>]) -> Matrix<T> {
+    let rows = mats.iter().map(|m| m.rows()).sum();
+    let cols = mats.iter().map(|m| m.cols()).sum();
+    let mut result = Matrix::new(rows, cols);
+
+    let (mut i0, mut j0) = (0, 0);
+    for m in mats {
+        for i in 0..m.rows() {
+            let start = (i0 + i) * cols + j0;
+            result.items[start..start + m.cols()].clone_from_slice(m.row(i));
+        }
+        i0 += m.rows();
+        j0 += m.cols();
+    }
+    result
+}
+
+/// Copies a flat fat pointer into a `rows×cols` matrix, in row-major order.
+/// The fat pointer must hold exactly `rows * cols` elements.
+pub fn reshape_to_matrix<T: Clone>(
+    flat: &Fat<T, usize>,
+    rows: usize,
+    cols: usize,
+) -> Result<Matrix<T>, ShapeError> {
+    if rows.checked_mul(cols) != Some(flat.len()) {
+        return Err(ShapeError);
+    }
+    Ok(Matrix {
+        items: flat.as_slice().to_vec(),
+        rows: rows.try_into().map_err(|_| ShapeError)?,
+        cols: cols.try_into().map_err(|_| ShapeError)?,
+    })
+}
+
+/// Computes the outer product of two vectors,
+/// producing an `a.len() × b.len()` matrix where `result[[i, j]] = a[i] * b[j]`.
+pub fn outer<T>(a: &[T], b: &[T]) -> Matrix<T>
+where
+    T: Copy + std::ops::Mul<Output = T>,
+{
+    let items: Vec<T> = a
+        .iter()
+        .flat_map(|&x| b.iter().map(move |&y| x * y))
+        .collect();
+
+    Matrix {
+        items,
+        rows: a.len().try_into().expect("`a` must fit in half a usize"),
+        cols: b.len().try_into().expect("`b` must fit in half a usize"),
+    }
+}
+
+/// Computes the dot product of two vectors.
+/// # Panics
+/// If the vectors have different lengths.
+pub fn dot<T>(a: &Fat<T, usize>, b: &Fat<T, usize>) -> T
+where
+    T: Copy + std::ops::Mul<Output = T> + std::iter::Sum,
+{
+    assert_eq!(a.len(), b.len(), "vectors must have the same length");
+    a.as_slice()
+        .iter()
+        .zip(b.as_slice())
+        .map(|(&x, &y)| x * y)
+        .sum()
+}
+
 fn main() {
-    let mut matrix = crate::Matrix::new(3, 4);
+    let mut matrix = Matrix::new(3, 4);
     matrix[[0, 1]] = 1;
     matrix[[2, 2]] = 4;
     assert_eq!(matrix[[0, 1]], 1);
@@ -211,4 +1127,310 @@ fn main() {
     assert_eq!(matrix2[[0, 0]], 0);
     assert_eq!(matrix2[[0, 1]], 1);
     assert_eq!(matrix2[[2, 2]], 4);
+
+    // The flat elements are in row-major order.
+    for (k, x) in mat.as_slice().iter().enumerate() {
+        assert_eq!(*x, mat[[k / mat.cols(), k % mat.cols()]]);
+    }
+
+    // Normalising rows in parallel matches doing it one row at a time.
+    let rows: Vec<Vec<f64>> = (0..8)
+        .map(|i| (0..5).map(|j| (i * 5 + j) as f64).collect())
+        .collect();
+    let mut seq = Matrix::from_nested(rows).unwrap();
+    let mut par = seq.to_owned();
+    seq.normalize_rows();
+    par.par_rows_mut().for_each(|row| {
+        let norm = row.iter().map(|x| x * x).sum::<f64>().sqrt();
+        if norm != 0.0 {
+            row.iter_mut().for_each(|x| *x /= norm);
+        }
+    });
+    assert!(seq.approx_eq(&par, 1e-12));
+
+    // Rotating by one moves the last row to the top.
+    let mut m = Matrix::from_nested(vec![vec![1, 2], vec![3, 4], vec![5, 6]]).unwrap();
+    m.rotate_rows(1);
+    assert_eq!(m.to_nested(), [[5, 6], [1, 2], [3, 4]]);
+    m.rotate_rows(3);
+    assert_eq!(m.to_nested(), [[5, 6], [1, 2], [3, 4]]);
+
+    // Vectors can be used as `n×1` or `1×n` matrices.
+    let col = Matrix::column_vec(vec![1, 2, 3]);
+    assert_eq!((col.rows(), col.cols()), (3, 1));
+    assert_eq!(col[[2, 0]], 3);
+    let row = Matrix::row_vec(vec![1, 2, 3]);
+    assert_eq!((row.rows(), row.cols()), (1, 3));
+    assert_eq!(row[[0, 1]], 2);
+
+    // The outer product of a 2-vector and a 3-vector is 2×3.
+    let m = outer(&[1, 2], &[3, 4, 5]);
+    assert_eq!(m.to_nested(), [[3, 4, 5], [6, 8, 10]]);
+
+    // Stacking checks that the matching dimension lines up.
+    let a = Matrix::from_nested(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+    let b = Matrix::from_nested(vec![vec![7, 8, 9], vec![10, 11, 12]]).unwrap();
+    let v = vstack(&[&a, &b]).unwrap();
+    assert_eq!((v.rows(), v.cols()), (4, 3));
+    assert_eq!(v[[2, 0]], 7);
+    let c = Matrix::from_nested(vec![vec![1, 2], vec![3, 4]]).unwrap();
+    let d = Matrix::from_nested(vec![vec![5, 6], vec![7, 8]]).unwrap();
+    let h = hstack(&[&c, &d]).unwrap();
+    assert_eq!(h.to_nested(), [[1, 2, 5, 6], [3, 4, 7, 8]]);
+    assert!(vstack(&[&a, &c]).is_err());
+    assert!(hstack(&[&c, &Matrix::row_vec(vec![1, 2])]).is_err());
+
+    // Columns are right-aligned to their widest cell.
+    let m = Matrix::from_nested(vec![vec![1, 200], vec![30, 4]]).unwrap();
+    assert_eq!(m.display().to_string(), " 1 200\n30   4");
+    assert_eq!(Matrix::<i32>::new(0, 0).display().to_string(), "");
+
+    // Collecting gives a column vector.
+    let m: Matrix<i32> = (0..6).collect();
+    assert_eq!((m.rows(), m.cols()), (6, 1));
+    assert_eq!(m[[4, 0]], 4);
+
+    // Broadcasting adds the vector to every row, or to every column.
+    let mut m = Matrix::from_nested(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+    m.add_row_vector(&[10, 20, 30]);
+    assert_eq!(m.to_nested(), [[11, 22, 33], [14, 25, 36]]);
+    m.add_col_vector(&[1, 2]);
+    assert_eq!(m.to_nested(), [[12, 23, 34], [16, 27, 38]]);
+
+    // Mapping rows keeps the shape when every row keeps its length.
+    let m = Matrix::from_nested(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+    let doubled = m
+        .map_rows(|row| row.iter().map(|x| x * 2).collect())
+        .unwrap();
+    assert_eq!(doubled.to_nested(), [[2, 4, 6], [8, 10, 12]]);
+    let ragged = m.map_rows(|row| vec![0; row[0] as usize]);
+    assert!(ragged.is_err());
+
+    // Scaling multiplies every element.
+    let mut m = Matrix::from_nested(vec![vec![1, 2], vec![3, 4]]).unwrap();
+    m *= 2;
+    assert_eq!(m.to_nested(), [[2, 4], [6, 8]]);
+
+    // The minor drops one row and one column.
+    let m = Matrix::from_nested(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]).unwrap();
+    let minor = m.minor(1, 0);
+    assert_eq!(minor.to_nested(), [[2, 3], [8, 9]]);
+
+    // Only the diagonal is filled.
+    let mut m = Matrix::<i32>::new(3, 3);
+    m.fill_diagonal(5);
+    assert_eq!(m.to_nested(), [[5, 0, 0], [0, 5, 0], [0, 0, 5]]);
+
+    // Comparing floats within a tolerance.
+    let a = Matrix::from_nested(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).unwrap();
+    let mut b = a.to_owned();
+    b[[1, 1]] += 1e-9;
+    assert!(a.approx_eq(&b, 1e-6));
+    assert!(!a.approx_eq(&b, 1e-12));
+    assert!(!a.approx_eq(&Matrix::row_vec(vec![1.0, 2.0, 3.0, 4.0]), 1.0));
+
+    // Folding over the rows can sum the whole matrix.
+    let m = Matrix::from_nested(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+    let total = m.fold_rows(0, |acc, row| acc + row.iter().sum::<i32>());
+    assert_eq!(total, 21);
+
+    // Row and column sums of a 2×3 matrix.
+    let m = Matrix::from_nested(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+    assert_eq!(m.row_sums(), [6, 15]);
+    assert_eq!(m.column_sums(), [5, 7, 9]);
+
+    // The Hadamard product multiplies matching elements.
+    let a = Matrix::from_nested(vec![vec![1, 2], vec![3, 4]]).unwrap();
+    let b = Matrix::from_nested(vec![vec![5, 6], vec![7, 8]]).unwrap();
+    let product = a.zip_with(&b, |x, y| x * y).unwrap();
+    assert_eq!(product.to_nested(), [[5, 12], [21, 32]]);
+    assert!(a
+        .zip_with(&Matrix::row_vec(vec![1, 2, 3, 4]), |x, y| x * y)
+        .is_err());
+
+    // Non-zero rows end up with unit length, and zero rows are left alone.
+    let mut m = Matrix::from_nested(vec![vec![3.0, 4.0], vec![0.0, 0.0]]).unwrap();
+    m.normalize_rows();
+    assert_eq!(m.to_nested(), [[0.6, 0.8], [0.0, 0.0]]);
+
+    // Clamping pulls every element into the range.
+    let mut m = Matrix::from_nested(vec![vec![-5, 3], vec![12, 10]]).unwrap();
+    m.clamp(0, 10);
+    assert_eq!(m.to_nested(), [[0, 3], [10, 10]]);
+
+    // Every cell is visited with its coordinates, in row-major order.
+    let mut m = Matrix::from_nested(vec![vec![7; 3]; 3]).unwrap();
+    let mut visited = Vec::new();
+    m.for_each_mut(|[i, j], x| {
+        visited.push([i, j]);
+        *x = i32::from(i == j);
+    });
+    assert_eq!(m.to_nested(), [[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+    assert_eq!(visited[..4], [[0, 0], [0, 1], [0, 2], [1, 0]]);
+
+    // The sum and mean of a 2×2 matrix.
+    let m = Matrix::from_nested(vec![vec![1.0, 2.0], vec![3.0, 6.0]]).unwrap();
+    assert_eq!(m.sum(), 12.0);
+    assert_eq!(m.mean(), 3.0);
+
+    // The largest element is found by its coordinates.
+    let m = Matrix::from_nested(vec![vec![1, 5, 2], vec![0, 3, 9], vec![4, 9, 8]]).unwrap();
+    assert_eq!(m.argmax(), Some([1, 2]));
+    assert_eq!(Matrix::<i32>::new(0, 3).argmax(), None);
+
+    // The dominant eigenvalue of [[2, 1], [1, 2]] is 3, with eigenvector (1, 1) / √2.
+    let m = Matrix::from_nested(vec![vec![2.0, 1.0], vec![1.0, 2.0]]).unwrap();
+    let (value, vector) = m.dominant_eigenpair(50);
+    assert!((value - 3.0_f64).abs() < 1e-9);
+    let expected = std::f64::consts::FRAC_1_SQRT_2;
+    assert!(vector.iter().all(|x| (x - expected).abs() < 1e-9));
+
+    // Diagonals above and below the main one stop at the edges.
+    let m = Matrix::from_nested(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]).unwrap();
+    assert_eq!(m.diagonal(0).copied().collect::<Vec<_>>(), [1, 5, 9]);
+    assert_eq!(m.diagonal(1).copied().collect::<Vec<_>>(), [2, 6]);
+    assert_eq!(m.diagonal(-2).copied().collect::<Vec<_>>(), [7]);
+    assert_eq!(m.diagonal(3).count(), 0);
+
+    // The elements come back in row-major order.
+    let m = Matrix::from_nested(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+    assert_eq!(m.into_vec(), [1, 2, 3, 4, 5, 6]);
+
+    // The identity has the same shape as the input.
+    let m = Matrix::<i32>::new(3, 3);
+    let id = m.identity_like();
+    assert_eq!(id.to_nested(), [[1, 0, 0], [0, 1, 0], [0, 0, 1]]);
+
+    // Each block sits on the diagonal, with zeros elsewhere.
+    let a = Matrix::from_nested(vec![vec![1, 2], vec![3, 4]]).unwrap();
+    let b = Matrix::from_nested(vec![vec![5]]).unwrap();
+    let m = block_diag(&[&a, &b]);
+    assert_eq!(m.to_nested(), [[1, 2, 0], [3, 4, 0], [0, 0, 5]]);
+
+    // Only the non-zero entries are collected.
+    let mut m = Matrix::<i32>::new(3, 3);
+    m[[0, 2]] = 7;
+    m[[2, 1]] = -1;
+    assert_eq!(m.to_sparse(), [([0, 2], 7), ([2, 1], -1)]);
+
+    // The entries are placed among zeros, and must be in bounds.
+    let m = Matrix::from_sparse(3, 3, &[([0, 2], 7), ([2, 1], -1)]).unwrap();
+    assert_eq!(m.to_nested(), [[0, 0, 7], [0, 0, 0], [0, -1, 0]]);
+    assert_eq!(m.to_sparse(), [([0, 2], 7), ([2, 1], -1)]);
+    assert!(Matrix::from_sparse(3, 3, &[([3, 0], 1)]).is_err());
+
+    // Bitwise operators work element by element.
+    let a = Matrix::from_nested(vec![vec![0b1100, 0b1010], vec![0b1111, 0]]).unwrap();
+    let b = Matrix::from_nested(vec![vec![0b1010, 0b1010], vec![0b0101, 0b1]]).unwrap();
+    assert_eq!((&a & &b).to_nested(), [[0b1000, 0b1010], [0b0101, 0]]);
+    assert_eq!((&a | &b).to_nested(), [[0b1110, 0b1010], [0b1111, 0b1]]);
+    assert_eq!((&a ^ &b).to_nested(), [[0b0110, 0], [0b1010, 0b1]]);
+
+    // A flat fat pointer can be reshaped when the lengths line up.
+    let flat = [1, 2, 3, 4, 5, 6];
+    let flat = Fat::from_slice(&flat, 6usize);
+    let m = reshape_to_matrix(flat, 2, 3).unwrap();
+    assert_eq!(m.to_nested(), [[1, 2, 3], [4, 5, 6]]);
+    assert!(reshape_to_matrix(flat, 4, 2).is_err());
+
+    // Row `i` of the result comes from row `perm[i]`.
+    let mut m = Matrix::from_nested(vec![vec![0, 0], vec![1, 1], vec![2, 2]]).unwrap();
+    m.permute_rows(&[2, 0, 1]);
+    assert_eq!(m.to_nested(), [[2, 2], [0, 0], [1, 1]]);
+    let mut m = Matrix::from_nested((0..5).map(|i| vec![i]).collect()).unwrap();
+    m.permute_rows(&[1, 0, 4, 2, 3]);
+    assert_eq!(m.into_vec(), [1, 0, 4, 2, 3]);
+
+    // sqrt(1 + 4 + 9 + 16) = sqrt(30)
+    let m = Matrix::from_nested(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).unwrap();
+    assert!((m.frobenius_norm() - 30.0_f64.sqrt()).abs() < 1e-12);
+
+    // Parsing reports where a row or number is wrong.
+    let m = Matrix::from_rows_str("1, 2, 3\n\n4.5,5,6\n").unwrap();
+    assert_eq!(m.to_nested(), [[1.0, 2.0, 3.0], [4.5, 5.0, 6.0]]);
+    assert!(matches!(
+        Matrix::from_rows_str("1,2\n3"),
+        Err(ParseError::Ragged { line: 2 })
+    ));
+    assert!(matches!(
+        Matrix::from_rows_str("1,2\n3,x"),
+        Err(ParseError::Number {
+            line: 2,
+            column: 2,
+            ..
+        })
+    ));
+    // Input too large to build here is rejected when its dimensions are packed.
+    assert!(matches!(
+        Matrix::parsed_dim(usize::MAX, 1),
+        Err(ParseError::TooLarge)
+    ));
+
+    // The corners swap across the anti-diagonal.
+    let m = Matrix::from_nested(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+    let t = m.anti_transpose();
+    assert_eq!((t.rows(), t.cols()), (3, 2));
+    assert_eq!(t.to_nested(), [[6, 3], [5, 2], [4, 1]]);
+
+    // Division and remainder work element by element.
+    let a = Matrix::from_nested(vec![vec![7, 8], vec![9, 10]]).unwrap();
+    let b = Matrix::from_nested(vec![vec![2, 3], vec![4, 5]]).unwrap();
+    assert_eq!((&a / &b).to_nested(), [[3, 2], [2, 2]]);
+    assert_eq!((&a % &b).to_nested(), [[1, 2], [1, 0]]);
+
+    // [1, 2, 3] · [4, 5, 6] = 32
+    let a = Fat::from_slice(&[1, 2, 3], 3usize);
+    let b = Fat::from_slice(&[4, 5, 6], 3usize);
+    assert_eq!(dot(a, b), 32);
+
+    // Nested vectors round-trip, and must be rectangular.
+    let rows = vec![vec![1, 2, 3], vec![4, 5, 6]];
+    let m = Matrix::from_nested(rows.clone()).unwrap();
+    assert_eq!((m.rows(), m.cols()), (2, 3));
+    assert_eq!(m.to_nested(), rows);
+    assert!(Matrix::from_nested(vec![vec![1, 2], vec![3]]).is_err());
+
+    // Tiling a 1×2 twice down and twice across gives a 2×4.
+    let m = Matrix::row_vec(vec![1, 2]);
+    assert_eq!(m.tile(2, 2).to_nested(), [[1, 2, 1, 2], [1, 2, 1, 2]]);
+
+    // Each block keeps the number of columns, and the last block is short.
+    let m: Matrix<i32> = Matrix::from_nested((0..5).map(|i| vec![i; 3]).collect()).unwrap();
+    let blocks: Vec<&Mat<i32>> = m.row_blocks(2).collect();
+    let dims: Vec<_> = blocks.iter().map(|b| (b.rows(), b.cols())).collect();
+    assert_eq!(dims, [(2, 3), (2, 3), (1, 3)]);
+    assert_eq!(blocks[2].as_slice(), &[4, 4, 4]);
+
+    // The kernel is flipped, so the output at [0, 0] is 1*4 + 2*3 + 4*2 + 5*1.
+    let m = Matrix::from_nested(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]).unwrap();
+    let kernel = Matrix::from_nested(vec![vec![1, 2], vec![3, 4]]).unwrap();
+    let out = m.convolve(&kernel);
+    assert_eq!((out.rows(), out.cols()), (2, 2));
+    assert_eq!(out[[0, 0]], 23);
+    assert_eq!(out[[1, 1]], 63);
+
+    // Padding one cell on each side puts the original in the middle.
+    let m = Matrix::from_nested(vec![vec![1, 2], vec![3, 4]]).unwrap();
+    let padded = m.pad(1, 1, 1, 1, 0);
+    assert_eq!(
+        padded.to_nested(),
+        [[0, 0, 0, 0], [0, 1, 2, 0], [0, 3, 4, 0], [0, 0, 0, 0]]
+    );
+
+    // Both halves can be written through at once.
+    // This also runs under Miri with `-Zmiri-tree-borrows`.
+    let mut m = Matrix::<i32>::new(4, 3);
+    let (top, bottom) = m.split_rows_mut(2);
+    assert_eq!((top.rows(), top.cols()), (2, 3));
+    assert_eq!((bottom.rows(), bottom.cols()), (2, 3));
+    top[[1, 2]] = 1;
+    bottom[[0, 0]] = 2;
+    bottom[[1, 2]] = 3;
+    assert_eq!(m.into_vec(), [0, 0, 0, 0, 0, 1, 2, 0, 0, 0, 0, 3]);
+
+    // The flat box holds the elements in row-major order.
+    let m = Matrix::from_nested(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+    let flat = m.to_flat_box();
+    assert_eq!(flat.meta(), 6);
+    assert_eq!(flat.as_slice(), &[1, 2, 3, 4, 5, 6]);
 }
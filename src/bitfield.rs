@@ -0,0 +1,181 @@
+/// Defines a metadata type that packs named bit fields into a `usize`.
+///
+/// Fields are listed from the lowest bits up, each with a width in bits.
+/// The last field may use `rest` as its width to take every remaining bit.
+/// The widths must not add up to more than the width of a `usize`.
+///
+/// One field may be marked `#[elem_count]` to implement [`ElemCount`](crate::ElemCount),
+/// using that field as the number of elements. Without it, the type only implements
+/// [`Meta`](crate::Meta), and `ElemCount` has to be implemented by hand.
+///
+/// ```
+/// use fat_ptr::Fat;
+///
+/// fat_ptr::bitfield_meta! {
+///     pub struct Header {
+///         version: 4,
+///         flags: 4,
+///         #[elem_count]
+///         count: rest,
+///     }
+/// }
+///
+/// let header = Header::new(1, 0b1010, 3).unwrap();
+/// assert_eq!(header.version(), 1);
+/// assert_eq!(header.flags(), 0b1010);
+/// assert_eq!(header.count(), 3);
+///
+/// let fat = Fat::from_slice(&[10, 20, 30], header);
+/// assert_eq!(fat.len(), 3);
+/// assert_eq!(fat.meta().flags(), 0b1010);
+/// ```
+///
+/// `new` returns `None` if any value doesn't fit in its field.
+#[macro_export]
+macro_rules! bitfield_meta {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_attr:ident])? $field:ident : $width:tt),+ $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        #[repr(transparent)]
+        $vis struct $name(usize);
+
+        impl $name {
+            /// Packs the fields, or returns `None` if any value is too wide for its field.
+            #[allow(clippy::too_many_arguments)]
+            $vis fn new($($field: usize),+) -> Option<Self> {
+                let mut bits = 0usize;
+                let mut offset = 0u32;
+                $(
+                    let width = $crate::bitfield_meta!(@width offset, $width);
+                    if $field & !$crate::bitfield_meta!(@mask width) != 0 {
+                        return None;
+                    }
+                    bits |= $field.checked_shl(offset).unwrap_or(0);
+                    offset += width;
+                )+
+                let _ = offset;
+                Some(Self(bits))
+            }
+        }
+
+        impl $crate::Meta for $name {
+            #[inline(always)]
+            fn into_bytes(self) -> usize {
+                self.0
+            }
+            #[inline(always)]
+            unsafe fn from_bytes(val: usize) -> Self {
+                Self(val)
+            }
+        }
+
+        $crate::bitfield_meta!(@getters $vis $name, 0u32, $($field : $width),+);
+        $($crate::bitfield_meta!(@elem_count $name, $field $(, $field_attr)?);)+
+    };
+
+    (@elem_count $name:ident, $field:ident, elem_count) => {
+        // SAFETY: The getter only reads the bits of the field,
+        // so it returns the same count for the same bits.
+        unsafe impl $crate::ElemCount for $name {
+            #[inline]
+            fn elem_count(&self) -> usize {
+                self.$field()
+            }
+        }
+    };
+    (@elem_count $name:ident, $field:ident) => {};
+
+    (@getters $vis:vis $name:ident, $offset:expr, $field:ident : rest) => {
+        $crate::bitfield_meta!(@getter $vis $name, $field, $offset, usize::BITS - ($offset));
+        $crate::bitfield_meta!(@getters $vis $name, $offset);
+    };
+    (@getters $vis:vis $name:ident, $offset:expr, $field:ident : $width:literal $(, $($rest:tt)+)?) => {
+        $crate::bitfield_meta!(@getter $vis $name, $field, $offset, $width);
+        $crate::bitfield_meta!(@getters $vis $name, $offset + $width $(, $($rest)+)?);
+    };
+    (@getters $vis:vis $name:ident, $offset:expr) => {
+        // A `rest` field makes the total exactly `usize::BITS`, which the compiler can see.
+        #[allow(unused_comparisons)]
+        const _: () = assert!(
+            $offset <= usize::BITS,
+            concat!("the fields of `", stringify!($name), "` are wider than a usize")
+        );
+    };
+
+    (@getter $vis:vis $name:ident, $field:ident, $offset:expr, $width:expr) => {
+        impl $name {
+            #[inline]
+            $vis fn $field(&self) -> usize {
+                self.0.checked_shr($offset).unwrap_or(0) & $crate::bitfield_meta!(@mask $width)
+            }
+        }
+    };
+
+    (@width $offset:expr, rest) => {
+        usize::BITS - $offset
+    };
+    (@width $offset:expr, $width:literal) => {
+        $width
+    };
+    (@mask $width:expr) => {
+        usize::MAX.checked_shr(usize::BITS - ($width)).unwrap_or(0)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ElemCount, Fat};
+
+    crate::bitfield_meta! {
+        struct Small {
+            a: 3,
+            b: 5,
+            rest: rest,
+        }
+    }
+
+    #[test]
+    fn new_rejects_values_too_wide() {
+        assert!(Small::new(0b1000, 0, 0).is_none());
+        assert!(Small::new(0, 0b10_0000, 0).is_none());
+        assert!(Small::new(0, 0, usize::MAX >> 7).is_none());
+
+        let small = Small::new(0b111, 0b1_1111, usize::MAX >> 8).unwrap();
+        assert_eq!(small.a(), 0b111);
+        assert_eq!(small.b(), 0b1_1111);
+        assert_eq!(small.rest(), usize::MAX >> 8);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn literal_field_fills_the_usize() {
+        crate::bitfield_meta! {
+            struct Full {
+                all: 64,
+            }
+        }
+        assert_eq!(Full::new(usize::MAX).unwrap().all(), usize::MAX);
+        assert_eq!(Full::new(0).unwrap().all(), 0);
+    }
+
+    #[test]
+    fn lone_elem_count_field() {
+        crate::bitfield_meta! {
+            struct Count {
+                #[elem_count]
+                count: rest,
+            }
+        }
+        let count = Count::new(usize::MAX).unwrap();
+        assert_eq!(count.elem_count(), usize::MAX);
+
+        let data = [1, 2, 3];
+        let fat = Fat::from_slice(&data, Count::new(2).unwrap());
+        assert_eq!(fat.as_slice(), &[1, 2]);
+    }
+}
@@ -0,0 +1,310 @@
+use std::alloc::{dealloc, Layout};
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::ptr::{self, NonNull};
+
+use crate::{ElemCount, Fat};
+
+/// An owning fat pointer to a heap allocation of values of type `T`,
+/// which stores metadata of type `M`.
+pub struct FatBox<T, M: ElemCount> {
+    ptr: NonNull<Fat<T, M>>,
+}
+
+impl<T, M: ElemCount> FatBox<T, M> {
+    /// Moves the elements of `data` into a new fat box.
+    /// # Panics
+    /// If `meta.elem_count()` is not the length of `data`.
+    pub fn from_vec(data: Vec<T>, meta: M) -> Self {
+        assert_eq!(
+            data.len(),
+            meta.elem_count(),
+            "metadata must count every element of `data`"
+        );
+        Self::from_boxed_slice(data.into_boxed_slice(), meta)
+    }
+
+    /// Creates a fat box of `meta.elem_count()` elements,
+    /// where each element is `f(index)`.
+    /// If `f` panics, the elements that were already created are leaked.
+    pub fn from_fn(meta: M, mut f: impl FnMut(usize) -> T) -> Self {
+        let mut data = Box::new_uninit_slice(meta.elem_count());
+        for (i, slot) in data.iter_mut().enumerate() {
+            slot.write(f(i));
+        }
+        // SAFETY: Every element was just initialized.
+        let data = unsafe { data.assume_init() };
+        Self::from_boxed_slice(data, meta)
+    }
+
+    /// Takes ownership of `meta.elem_count()` values of `T` at `ptr`.
+    /// # Safety
+    /// `ptr` must point to that many initialized values of `T`, which must not be used again
+    /// except through the returned box.
+    /// If `Layout::array::<T>(meta.elem_count())` has a non-zero size, the memory must have been
+    /// allocated by the global allocator with exactly that layout.
+    /// Otherwise, `ptr` must be non-null and aligned, and it is never deallocated.
+    pub unsafe fn from_raw(ptr: *mut T, meta: M) -> Self {
+        let fat =
+            ptr::slice_from_raw_parts_mut(ptr as *mut (), meta.into_bytes()) as *mut Fat<T, M>;
+        // SAFETY: The caller guarantees that the pointer is not null.
+        let ptr = unsafe { NonNull::new_unchecked(fat) };
+        Self { ptr }
+    }
+
+    /// Moves the elements of this fat box into a vector, discarding the metadata.
+    pub fn into_vec(self) -> Vec<T> {
+        let len = self.len();
+        let data = ManuallyDrop::new(self).ptr.as_ptr() as *mut T;
+        // SAFETY: The allocation came from a `Box<[T]>` of length `len`,
+        // and `self` won't drop the elements or free the memory.
+        let data = unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(data, len)) };
+        data.into_vec()
+    }
+
+    /// Takes ownership of `data`, whose length must be `meta.elem_count()`.
+    fn from_boxed_slice(data: Box<[T]>, meta: M) -> Self {
+        debug_assert_eq!(data.len(), meta.elem_count());
+        let data = Box::into_raw(data) as *mut ();
+        // The length of the fat pointer holds the metadata, not the number of elements.
+        let fat = ptr::slice_from_raw_parts_mut(data, meta.into_bytes()) as *mut Fat<T, M>;
+        // SAFETY: The pointer came from a `Box`, so it is not null.
+        let ptr = unsafe { NonNull::new_unchecked(fat) };
+        Self { ptr }
+    }
+}
+
+impl<T> FatBox<T, usize> {
+    /// Changes the number of elements to `new_count`, like [`Vec::resize`].
+    /// Extra elements are dropped, and new ones are clones of `fill`.
+    pub fn resize(&mut self, new_count: usize, fill: T)
+    where
+        T: Clone,
+    {
+        let mut items = std::mem::replace(self, Self::from_vec(Vec::new(), 0)).into_vec();
+        items.resize(new_count, fill);
+        *self = Self::from_vec(items, new_count);
+    }
+}
+
+/// Collects the items into a fat box, with the number of items as its metadata.
+impl<T> FromIterator<T> for FatBox<T, usize> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        // Collecting into a `Vec` first uses the iterator's size hint to allocate.
+        let items: Vec<T> = iter.into_iter().collect();
+        let count = items.len();
+        Self::from_vec(items, count)
+    }
+}
+
+// SAFETY: A `FatBox` owns its elements, like a `Box<[T]>`.
+// The metadata is only ever copied out by value, so it just has to be `Send`.
+unsafe impl<T: Send, M: ElemCount + Send> Send for FatBox<T, M> {}
+// SAFETY: Shared access only hands out `&T` and copies of the metadata.
+unsafe impl<T: Sync, M: ElemCount + Send> Sync for FatBox<T, M> {}
+
+impl<T, M: ElemCount> Deref for FatBox<T, M> {
+    type Target = Fat<T, M>;
+    fn deref(&self) -> &Fat<T, M> {
+        // SAFETY: `ptr` points to an allocation that we own, holding
+        // `elem_count` initialized values of T.
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T, M: ElemCount> DerefMut for FatBox<T, M> {
+    fn deref_mut(&mut self) -> &mut Fat<T, M> {
+        // SAFETY: `ptr` points to an allocation that we own, holding
+        // `elem_count` initialized values of T.
+        // We have unique access to it through `&mut self`.
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T, M: ElemCount> Drop for FatBox<T, M> {
+    fn drop(&mut self) {
+        // `ElemCount` guarantees that the count decoded from the metadata
+        // is the same count the allocation was created with.
+        let len = self.len();
+        let data = self.ptr.as_ptr() as *mut T;
+
+        // SAFETY: `data` points to `len` initialized values of T that we own,
+        // and they are never used again.
+        unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(data, len)) };
+
+        // The allocation came from a `Box<[T]>` of length `len`, which has this layout.
+        // That box doesn't allocate if the layout is zero-sized,
+        // which happens when `T` is a ZST or `len` is zero.
+        let layout = Layout::array::<T>(len).expect("allocation was created with this layout");
+        if layout.size() != 0 {
+            // SAFETY: The memory was allocated by the global allocator with `layout`.
+            unsafe { dealloc(data as *mut u8, layout) };
+        }
+    }
+}
+
+/// A fat box that overwrites its elements with zeroes before they are dropped,
+/// so that secrets don't linger in freed memory.
+#[cfg(feature = "zeroize")]
+pub struct ZeroizeOnDrop<T: zeroize::Zeroize, M: ElemCount>(FatBox<T, M>);
+
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize, M: ElemCount> ZeroizeOnDrop<T, M> {
+    /// Wraps `data`, so that its elements are zeroed when it is dropped.
+    pub fn new(data: FatBox<T, M>) -> Self {
+        Self(data)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize, M: ElemCount> Deref for ZeroizeOnDrop<T, M> {
+    type Target = Fat<T, M>;
+    fn deref(&self) -> &Fat<T, M> {
+        &self.0
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize, M: ElemCount> DerefMut for ZeroizeOnDrop<T, M> {
+    fn deref_mut(&mut self) -> &mut Fat<T, M> {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize, M: ElemCount> Drop for ZeroizeOnDrop<T, M> {
+    fn drop(&mut self) {
+        // The box itself frees the memory once this returns.
+        for x in self.0.as_mut_slice() {
+            x.zeroize();
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: zeroize::Zeroize, M: ElemCount> zeroize::ZeroizeOnDrop for ZeroizeOnDrop<T, M> {}
+
+#[cfg(test)]
+mod tests {
+    // These also run under Miri with `-Zmiri-tree-borrows`; see the note on `Fat`.
+    use super::*;
+    use std::cell::Cell;
+
+    /// Counts how many times values of it are dropped.
+    struct Counted<'a>(&'a Cell<usize>);
+
+    impl Drop for Counted<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn drops_each_element_once() {
+        let drops = Cell::new(0);
+        let data: Vec<_> = (0..4).map(|_| Counted(&drops)).collect();
+        let boxed = FatBox::from_vec(data, 4usize);
+        assert_eq!(drops.get(), 0);
+        drop(boxed);
+        assert_eq!(drops.get(), 4);
+    }
+
+    #[test]
+    fn drops_zero_sized_elements() {
+        thread_local!(static DROPS: Cell<usize> = const { Cell::new(0) });
+        struct Zst;
+        impl Drop for Zst {
+            fn drop(&mut self) {
+                DROPS.with(|d| d.set(d.get() + 1));
+            }
+        }
+
+        let boxed = FatBox::from_vec((0..3).map(|_| Zst).collect(), 3usize);
+        assert_eq!(boxed.len(), 3);
+        drop(boxed);
+        assert_eq!(DROPS.with(Cell::get), 3);
+    }
+
+    #[test]
+    fn empty_box() {
+        let drops = Cell::new(0);
+        let boxed = FatBox::<Counted, usize>::from_vec(Vec::new(), 0);
+        assert!(boxed.is_empty());
+        drop(boxed);
+        assert_eq!(drops.get(), 0);
+    }
+
+    #[test]
+    fn from_vec_round_trip() {
+        let mut boxed = FatBox::from_vec(vec![String::from("a"), String::from("b")], 2usize);
+        assert_eq!(boxed.get(1).map(String::as_str), Some("b"));
+        boxed.as_mut_slice()[0].push('!');
+        assert_eq!(boxed.meta(), 2);
+        assert_eq!(boxed.into_vec(), ["a!", "b"]);
+    }
+
+    #[test]
+    fn is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FatBox<String, usize>>();
+    }
+
+    #[test]
+    fn from_fn_indices() {
+        let boxed = FatBox::from_fn(5usize, |i| i);
+        assert_eq!(boxed.as_slice(), &[0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn from_raw_adopts_allocation() {
+        let drops = Cell::new(0);
+        let layout = Layout::array::<Counted>(3).unwrap();
+        // SAFETY: The layout is not zero-sized, every element is written before
+        // the box is created, and the memory is only used through the box.
+        let boxed = unsafe {
+            let data = std::alloc::alloc(layout) as *mut Counted;
+            assert!(!data.is_null());
+            for i in 0..3 {
+                data.add(i).write(Counted(&drops));
+            }
+            FatBox::from_raw(data, 3usize)
+        };
+        assert_eq!(boxed.len(), 3);
+        drop(boxed);
+        assert_eq!(drops.get(), 3);
+    }
+
+    #[test]
+    fn from_raw_zero_sized() {
+        // SAFETY: Zero-sized layouts only need a non-null, aligned pointer.
+        let empty = unsafe { FatBox::<u64, usize>::from_raw(NonNull::dangling().as_ptr(), 0) };
+        assert!(empty.is_empty());
+        drop(empty);
+
+        // SAFETY: Same as above, and `()` needs no initialization.
+        let units = unsafe { FatBox::<(), usize>::from_raw(NonNull::dangling().as_ptr(), 4) };
+        assert_eq!(units.len(), 4);
+        drop(units);
+    }
+
+    #[test]
+    fn resize_grows_and_shrinks() {
+        let mut boxed = FatBox::from_vec(vec![String::from("a"), String::from("b")], 2usize);
+        boxed.resize(4, String::from("x"));
+        assert_eq!(boxed.meta(), 4);
+        assert_eq!(boxed.as_slice(), &["a", "b", "x", "x"]);
+        boxed.resize(1, String::new());
+        assert_eq!(boxed.meta(), 1);
+        assert_eq!(boxed.as_slice(), &["a"]);
+    }
+
+    #[test]
+    fn collect_from_iterator() {
+        let boxed: FatBox<String, usize> = (0..3).map(|i| i.to_string()).collect();
+        assert_eq!(boxed.meta(), 3);
+        assert_eq!(boxed.as_slice(), &["0", "1", "2"]);
+
+        let empty: FatBox<i32, usize> = std::iter::empty().collect();
+        assert!(empty.is_empty());
+    }
+}
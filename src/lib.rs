@@ -1,8 +1,24 @@
 use std::marker::PhantomData;
 
+mod bitfield;
+mod boxed;
+pub use boxed::FatBox;
+#[cfg(feature = "zeroize")]
+pub use boxed::ZeroizeOnDrop;
+
+/// Derives [`Meta`] for a `#[repr(C)]` or `#[repr(transparent)]` struct
+/// that is exactly the width of a `usize`, and whose fields are all [`NoPadding`].
+#[cfg(feature = "derive")]
+pub use fat_ptr_derive::Meta;
+
 /// A fat pointer to zero or more values of type `T`,
 /// which stores arbitrary metadata of type `M`.
 /// M must be trivially convertable to and from a `usize`.
+///
+/// Element pointers are derived from the zero-sized `[()]` field. Miri's default
+/// Stacked Borrows model gives a reference to a zero-sized value no access to the
+/// memory behind it, so it reports every element access as undefined behavior.
+/// Under Tree Borrows (`MIRIFLAGS=-Zmiri-tree-borrows`), the accesses are allowed.
 #[repr(transparent)]
 pub struct Fat<T, M: Meta>(PhantomData<T>, PhantomData<*mut M>, [()]);
 
@@ -19,13 +35,117 @@ pub trait Meta: Sized {
     unsafe fn from_bytes(_: usize) -> Self;
 }
 
+/// Metadata that knows how many values of `T` live behind the fat pointer.
+/// # Safety
+/// `elem_count` must always return the same value for the same metadata,
+/// including after it has been passed through `into_bytes` and `from_bytes`.
+/// The safe accessors on [`Fat`] rely on this to stay in bounds.
+pub unsafe trait ElemCount: Meta {
+    /// Gets the number of elements behind a fat pointer with this metadata.
+    fn elem_count(&self) -> usize;
+}
+
+/// Metadata that can describe any leading run of the elements it counts,
+/// which lets fat pointers be split into smaller fat pointers.
+pub trait SplitMeta: ElemCount {
+    /// Creates metadata like `self`, but for `count` elements.
+    /// The result's `elem_count` must be `count`.
+    fn with_elem_count(&self, count: usize) -> Self;
+}
+
+/// A type with no padding, so every byte of a value is initialized.
+/// [`Meta`] can only be derived for structs whose fields are all `NoPadding`.
+/// # Safety
+/// No value of the type may contain uninitialized bytes,
+/// including padding anywhere inside it.
+pub unsafe trait NoPadding: Copy {}
+
+macro_rules! impl_no_padding {
+    ($($t:ty),*) => {$(
+        // SAFETY: Primitive types have no padding.
+        unsafe impl NoPadding for $t {}
+    )*};
+}
+impl_no_padding!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+impl_no_padding!(f32, f64, bool, char, ());
+impl_no_padding!(
+    std::num::NonZeroU8,
+    std::num::NonZeroU16,
+    std::num::NonZeroU32,
+    std::num::NonZeroU64,
+    std::num::NonZeroU128,
+    std::num::NonZeroUsize,
+    std::num::NonZeroI8,
+    std::num::NonZeroI16,
+    std::num::NonZeroI32,
+    std::num::NonZeroI64,
+    std::num::NonZeroI128,
+    std::num::NonZeroIsize
+);
+
+// SAFETY: Arrays have no padding between their elements.
+unsafe impl<T: NoPadding, const N: usize> NoPadding for [T; N] {}
+
+impl Meta for usize {
+    #[inline(always)]
+    fn into_bytes(self) -> usize {
+        self
+    }
+    #[inline(always)]
+    unsafe fn from_bytes(val: usize) -> Self {
+        val
+    }
+}
+
+// SAFETY: The count is the value itself.
+unsafe impl ElemCount for usize {
+    #[inline(always)]
+    fn elem_count(&self) -> usize {
+        *self
+    }
+}
+
+impl SplitMeta for usize {
+    #[inline(always)]
+    fn with_elem_count(&self, count: usize) -> Self {
+        count
+    }
+}
+
+#[cfg(target_pointer_width = "64")]
+impl Meta for std::num::NonZeroU32 {
+    #[inline(always)]
+    fn into_bytes(self) -> usize {
+        // A u32 fits in the low bits of a 64-bit usize.
+        self.get() as usize
+    }
+    #[inline(always)]
+    unsafe fn from_bytes(val: usize) -> Self {
+        // SAFETY: `val` came from `into_bytes`, so it is the value
+        // of a `NonZeroU32`, which fits in a u32 and is not zero.
+        std::num::NonZeroU32::new_unchecked(val as u32)
+    }
+}
+
+// SAFETY: The count is the value itself.
+#[cfg(target_pointer_width = "64")]
+unsafe impl ElemCount for std::num::NonZeroU32 {
+    #[inline(always)]
+    fn elem_count(&self) -> usize {
+        self.get() as usize
+    }
+}
+
 impl<T, M: Meta> Fat<T, M> {
+    #[inline(always)]
     pub fn ptr(&self) -> *const T {
         self.2.as_ptr() as *const T
     }
+    #[inline(always)]
     pub fn mut_ptr(&mut self) -> *mut T {
         self.2.as_mut_ptr() as *mut T
     }
+    #[inline(always)]
     pub fn meta(&self) -> M {
         // `Fat` can only be created by the `from_*` fns, which all use
         // `Meta::into_bytes` on the metadata.
@@ -33,26 +153,1363 @@ impl<T, M: Meta> Fat<T, M> {
         unsafe { M::from_bytes(self.2.len()) }
     }
 
-    pub fn from_slice(data: &[T], meta: M) -> &Self {
+    /// Gets the zero-sized slice that this type wraps.
+    /// Its length is the packed metadata, as returned by `Meta::into_bytes`,
+    /// not the number of elements.
+    ///
+    /// This is a low-level window into the representation, mostly useful for debugging.
+    pub fn as_unit_slice(&self) -> &[()] {
+        &self.2
+    }
+
+    /// Creates a fat pointer to `data` without checking the metadata against it.
+    /// # Safety
+    /// If `M` implements [`ElemCount`], `meta.elem_count()` must not be
+    /// greater than `data.len()`.
+    pub unsafe fn from_slice_unchecked(data: &[T], meta: M) -> &Self {
         let ptr = data.as_ptr() as *const ();
         // SAFETY: Creating this slice is sound, as `slice::from_raw_parts` requires
         // `ptr` to point to `dim` fully-initialized and aligned values of ().
         // () is a ZST, so it is fully initialized and aligned no matter what.
-        let fat = unsafe { std::slice::from_raw_parts(ptr, meta.into_bytes()) };
+        let fat = std::slice::from_raw_parts(ptr, meta.into_bytes());
 
         // SAFETY: `Fat` is repr(transparent), so it's sound to transmute
         // from &[()] -> &Fat<T, M>.
-        unsafe { std::mem::transmute(fat) }
+        std::mem::transmute(fat)
     }
-    pub fn from_slice_mut(data: &mut [T], meta: M) -> &mut Self {
+    /// Creates a mutable fat pointer to `data` without checking the metadata against it.
+    /// # Safety
+    /// If `M` implements [`ElemCount`], `meta.elem_count()` must not be
+    /// greater than `data.len()`.
+    pub unsafe fn from_slice_mut_unchecked(data: &mut [T], meta: M) -> &mut Self {
         let ptr = data.as_mut_ptr() as *mut ();
         // SAFETY: Creating this slice is sound, as `slice::from_raw_parts_mut` requires
         // `ptr` to point to `dim` fully-initialized and aligned values of ().
         // () is a ZST, so it is fully initialized and aligned no matter what.
-        let fat = unsafe { std::slice::from_raw_parts_mut(ptr, meta.into_bytes()) };
+        let fat = std::slice::from_raw_parts_mut(ptr, meta.into_bytes());
 
         // SAFETY: `Fat` is repr(transparent), so it's sound to transmute
         // from &mut [()] -> &mut Fat<T, M>.
-        unsafe { std::mem::transmute(fat) }
+        std::mem::transmute(fat)
+    }
+}
+
+impl<T, M: ElemCount> Fat<T, M> {
+    /// Creates a fat pointer to the first `meta.elem_count()` values in `data`.
+    /// # Panics
+    /// If `data` is shorter than `meta.elem_count()`.
+    pub fn from_slice(data: &[T], meta: M) -> &Self {
+        assert!(
+            meta.elem_count() <= data.len(),
+            "metadata refers to more elements than `data` holds"
+        );
+        // SAFETY: We just checked the count against `data`.
+        unsafe { Self::from_slice_unchecked(data, meta) }
+    }
+    /// Creates a mutable fat pointer to the first `meta.elem_count()` values in `data`.
+    /// # Panics
+    /// If `data` is shorter than `meta.elem_count()`.
+    pub fn from_slice_mut(data: &mut [T], meta: M) -> &mut Self {
+        assert!(
+            meta.elem_count() <= data.len(),
+            "metadata refers to more elements than `data` holds"
+        );
+        // SAFETY: We just checked the count against `data`.
+        unsafe { Self::from_slice_mut_unchecked(data, meta) }
+    }
+
+    /// Gets the number of elements behind this fat pointer.
+    pub fn len(&self) -> usize {
+        self.meta().elem_count()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        // Note that the length of the inner slice is the packed metadata,
+        // not the number of elements.
+        let len = self.len();
+        if len == 0 {
+            return &[];
+        }
+        // SAFETY: `Fat` can only be safely created by `from_slice`, which checks
+        // that `ptr` points to at least `elem_count` initialized values of T.
+        // The count is always the same for the same metadata, per `ElemCount`.
+        unsafe { std::slice::from_raw_parts(self.ptr(), len) }
+    }
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        let len = self.len();
+        if len == 0 {
+            return &mut [];
+        }
+        // SAFETY: `Fat` can only be safely created by `from_slice_mut`, which checks
+        // that `ptr` points to at least `elem_count` initialized values of T.
+        // The count is always the same for the same metadata, per `ElemCount`.
+        unsafe { std::slice::from_raw_parts_mut(self.mut_ptr(), len) }
+    }
+
+    pub fn get(&self, i: usize) -> Option<&T> {
+        self.as_slice().get(i)
+    }
+
+    /// Gets the element `i` places from the end, where `0` is the last element.
+    pub fn get_from_end(&self, i: usize) -> Option<&T> {
+        let index = self.len().checked_sub(i)?.checked_sub(1)?;
+        self.get(index)
+    }
+
+    /// Iterates over the elements of this fat pointer, from last to first.
+    pub fn iter_rev(&self) -> impl DoubleEndedIterator<Item = &T> {
+        self.as_slice().iter().rev()
+    }
+
+    /// Copies the elements of this fat pointer `n` times into a new fat box.
+    /// # Panics
+    /// If the total count overflows a `usize`.
+    pub fn repeat(&self, n: usize) -> FatBox<T, usize>
+    where
+        T: Clone,
+    {
+        let count = self
+            .len()
+            .checked_mul(n)
+            .expect("repeated count overflows a usize");
+        let mut items = Vec::with_capacity(count);
+        for _ in 0..n {
+            items.extend_from_slice(self.as_slice());
+        }
+        FatBox::from_vec(items, count)
+    }
+
+    /// Iterates over every `step`-th element, starting with the first.
+    /// # Panics
+    /// If `step` is zero.
+    pub fn iter_step(&self, step: usize) -> impl Iterator<Item = &T> {
+        assert!(step != 0, "`step` must not be zero");
+        self.as_slice().iter().step_by(step)
+    }
+
+    /// Iterates over mutable references to the elements, along with their indices.
+    pub fn iter_mut_indexed(&mut self) -> impl Iterator<Item = (usize, &mut T)> {
+        self.as_mut_slice().iter_mut().enumerate()
+    }
+
+    /// Gets the first `N` elements as an array, or `None` if there are fewer than `N`.
+    pub fn first_chunk<const N: usize>(&self) -> Option<&[T; N]> {
+        self.as_slice().first_chunk()
+    }
+    /// Gets the last `N` elements as an array, or `None` if there are fewer than `N`.
+    pub fn last_chunk<const N: usize>(&self) -> Option<&[T; N]> {
+        self.as_slice().last_chunk()
+    }
+
+    /// Copies the elements into two new fat boxes: those that match `pred`,
+    /// and those that don't.
+    pub fn partition_to_boxes(
+        &self,
+        mut pred: impl FnMut(&T) -> bool,
+    ) -> (FatBox<T, usize>, FatBox<T, usize>)
+    where
+        T: Clone,
+    {
+        let (yes, no): (Vec<T>, Vec<T>) = self.as_slice().iter().cloned().partition(|x| pred(x));
+        let (yes_len, no_len) = (yes.len(), no.len());
+        (FatBox::from_vec(yes, yes_len), FatBox::from_vec(no, no_len))
+    }
+
+    /// Shifts the elements left by `by` places, dropping the first `by` elements
+    /// and filling the end with copies of `fill`. `by` is clamped to the length.
+    pub fn shift_left(&mut self, by: usize, fill: T)
+    where
+        T: Clone,
+    {
+        let slice = self.as_mut_slice();
+        let by = by.min(slice.len());
+        slice.rotate_left(by);
+        let len = slice.len();
+        slice[len - by..].fill(fill);
+    }
+
+    /// Hashes the count and every element with a fresh `H`.
+    pub fn checksum<H: std::hash::Hasher + Default>(&self) -> u64
+    where
+        T: std::hash::Hash,
+    {
+        use std::hash::Hash;
+        let mut hasher = H::default();
+        // Hashing a slice includes its length.
+        self.as_slice().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Gets the `N` elements starting at `start` as an array,
+    /// or `None` if they would run past the end.
+    pub fn array_ref_at<const N: usize>(&self, start: usize) -> Option<&[T; N]> {
+        self.as_slice().get(start..)?.first_chunk()
+    }
+
+    /// Gets the number of elements, but no more than `max`.
+    /// Useful for capping loops when the metadata can't be trusted to be small.
+    pub fn count_saturating(&self, max: usize) -> usize {
+        self.len().min(max)
+    }
+
+    /// Sorts the elements by a key, calling `f` only once per element.
+    pub fn sort_by_cached_key<K: Ord>(&mut self, f: impl FnMut(&T) -> K) {
+        self.as_mut_slice().sort_by_cached_key(f);
+    }
+
+    /// Gets the largest element according to `cmp`, or `None` if there are no elements.
+    pub fn max_by(&self, mut cmp: impl FnMut(&T, &T) -> std::cmp::Ordering) -> Option<&T> {
+        self.as_slice().iter().max_by(|a, b| cmp(a, b))
+    }
+    /// Gets the smallest element according to `cmp`, or `None` if there are no elements.
+    pub fn min_by(&self, mut cmp: impl FnMut(&T, &T) -> std::cmp::Ordering) -> Option<&T> {
+        self.as_slice().iter().min_by(|a, b| cmp(a, b))
+    }
+
+    /// Creates a fat box where each element is the sum of
+    /// this fat pointer's elements up to and including the same index.
+    pub fn prefix_sums(&self) -> FatBox<T, usize>
+    where
+        T: Clone + std::ops::Add<Output = T>,
+    {
+        let mut sums: Vec<T> = Vec::with_capacity(self.len());
+        for x in self.as_slice() {
+            let sum = match sums.last() {
+                Some(prev) => prev.clone() + x.clone(),
+                None => x.clone(),
+            };
+            sums.push(sum);
+        }
+        let count = sums.len();
+        FatBox::from_vec(sums, count)
+    }
+
+    /// Gets the indices that would sort the elements, without moving them.
+    /// Equal elements keep their original order.
+    pub fn sort_indices(&self) -> Vec<usize>
+    where
+        T: Ord,
+    {
+        let slice = self.as_slice();
+        let mut indices: Vec<usize> = (0..slice.len()).collect();
+        indices.sort_by_key(|&i| &slice[i]);
+        indices
+    }
+
+    /// Gets the smallest and largest elements in a single pass,
+    /// or `None` if there are no elements.
+    /// Ties go to the first smallest and the last largest, like `Iterator::min` and `max`.
+    pub fn min_max(&self) -> Option<(&T, &T)>
+    where
+        T: Ord,
+    {
+        let (first, rest) = self.as_slice().split_first()?;
+        Some(rest.iter().fold((first, first), |(min, max), x| {
+            (
+                if x < min { x } else { min },
+                if x >= max { x } else { max },
+            )
+        }))
+    }
+
+    /// Reorders the elements so that every element that matches `pred` comes first,
+    /// and returns how many elements matched. The order within each group is unspecified.
+    pub fn partition_in_place(&mut self, mut pred: impl FnMut(&T) -> bool) -> usize {
+        let slice = self.as_mut_slice();
+        let mut split = 0;
+        for i in 0..slice.len() {
+            if pred(&slice[i]) {
+                slice.swap(split, i);
+                split += 1;
+            }
+        }
+        split
+    }
+
+    /// Clones the elements in `range` into a new fat box.
+    /// # Panics
+    /// If `range` is out of bounds.
+    pub fn clone_range_to_box(&self, range: std::ops::Range<usize>) -> FatBox<T, usize>
+    where
+        T: Clone,
+    {
+        let items = self.as_slice()[range].to_vec();
+        let count = items.len();
+        FatBox::from_vec(items, count)
+    }
+
+    /// Checks whether this fat pointer has the same elements as `other`,
+    /// whose metadata may be of a different type.
+    pub fn data_eq<M2: ElemCount>(&self, other: &Fat<T, M2>) -> bool
+    where
+        T: PartialEq,
+    {
+        self.as_slice() == other.as_slice()
+    }
+
+    /// Clones the leading elements that match `pred` into a new fat box,
+    /// stopping at the first element that doesn't.
+    pub fn take_while_to_box(&self, mut pred: impl FnMut(&T) -> bool) -> FatBox<T, usize>
+    where
+        T: Clone,
+    {
+        let items: Vec<T> = self
+            .as_slice()
+            .iter()
+            .take_while(|x| pred(x))
+            .cloned()
+            .collect();
+        let count = items.len();
+        FatBox::from_vec(items, count)
+    }
+
+    /// Sums the result of `weight` for each element.
+    pub fn weighted_sum<K: std::iter::Sum>(&self, weight: impl FnMut(&T) -> K) -> K {
+        self.as_slice().iter().map(weight).sum()
+    }
+
+    /// Gets the index of the smallest element, or the first one if there is a tie.
+    /// Returns `None` if this fat pointer is empty.
+    pub fn position_min(&self) -> Option<usize>
+    where
+        T: Ord,
+    {
+        let (i, _) = self.as_slice().iter().enumerate().min_by_key(|&(_, x)| x)?;
+        Some(i)
+    }
+    /// Gets the index of the largest element, or the first one if there is a tie.
+    /// This differs from [`Iterator::max_by`], which returns the last of equal elements,
+    /// so that ties break the same way as in [`position_min`](Self::position_min).
+    /// Returns `None` if this fat pointer is empty.
+    pub fn position_max(&self) -> Option<usize>
+    where
+        T: Ord,
+    {
+        // `max_by_key` picks the last of equal elements, so search from the end.
+        let (i, _) = self
+            .as_slice()
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|&(_, x)| x)?;
+        Some(i)
+    }
+
+    /// Clones the first occurrence of each distinct element into a new fat box,
+    /// keeping them in order.
+    pub fn unique_to_box(&self) -> FatBox<T, usize>
+    where
+        T: Clone + std::hash::Hash + Eq,
+    {
+        let mut seen = std::collections::HashSet::new();
+        let items: Vec<T> = self
+            .as_slice()
+            .iter()
+            .filter(|x| seen.insert(*x))
+            .cloned()
+            .collect();
+        let count = items.len();
+        FatBox::from_vec(items, count)
+    }
+
+    /// Calls `f` on each element in order, stopping at the first error.
+    pub fn try_for_each<E>(&self, f: impl FnMut(&T) -> Result<(), E>) -> Result<(), E> {
+        self.as_slice().iter().try_for_each(f)
+    }
+
+    /// Iterates over copies of the elements.
+    pub fn copied_elements(&self) -> impl Iterator<Item = T> + '_
+    where
+        T: Copy,
+    {
+        self.as_slice().iter().copied()
+    }
+
+    /// Binary searches elements sorted by the key that `f` extracts, for the key `b`.
+    /// See [`slice::binary_search_by_key`] for what is returned.
+    pub fn binary_search_by_key<B: Ord>(
+        &self,
+        b: &B,
+        f: impl FnMut(&T) -> B,
+    ) -> Result<usize, usize> {
+        self.as_slice().binary_search_by_key(b, f)
+    }
+
+    /// Checks whether `other` yields exactly the elements of this fat pointer, in order.
+    pub fn iter_eq<I: IntoIterator<Item = T>>(&self, other: I) -> bool
+    where
+        T: PartialEq,
+    {
+        let mut other = other.into_iter();
+        self.as_slice()
+            .iter()
+            .all(|x| other.next().is_some_and(|y| *x == y))
+            && other.next().is_none()
+    }
+}
+
+macro_rules! impl_float_fat {
+    ($($t:ty),*) => {$(
+        impl<M: ElemCount> Fat<$t, M> {
+            /// Checks if `other` has the same number of elements as this fat pointer,
+            /// and every element is within `tol` of the corresponding element of `other`.
+            pub fn approx_eq(&self, other: &Fat<$t, M>, tol: $t) -> bool {
+                self.len() == other.len()
+                    && self
+                        .as_slice()
+                        .iter()
+                        .zip(other.as_slice())
+                        .all(|(a, b)| (a - b).abs() <= tol)
+            }
+
+            /// Creates a fat box of the averages of every window of `window` elements.
+            /// # Panics
+            /// If `window` is zero or greater than `len()`.
+            pub fn moving_average(&self, window: usize) -> FatBox<$t, usize> {
+                assert!(
+                    window != 0 && window <= self.len(),
+                    "`window` must be between 1 and the number of elements"
+                );
+                let averages: Vec<$t> = self
+                    .as_slice()
+                    .windows(window)
+                    .map(|w| w.iter().sum::<$t>() / window as $t)
+                    .collect();
+                let count = averages.len();
+                FatBox::from_vec(averages, count)
+            }
+        }
+    )*};
+}
+impl_float_fat!(f32, f64);
+
+macro_rules! impl_atomic_fat {
+    ($($t:ty => $atomic:ty),*) => {$(
+        impl<M: ElemCount> Fat<$t, M> {
+            /// Gets the elements of this fat pointer as atomics,
+            #[doc = concat!("so that they can be updated from several threads through a shared `&[", stringify!($atomic), "]`.")]
+            /// This takes `&mut self` so that nothing can read the elements
+            /// non-atomically while the atomics are borrowed.
+            pub fn as_atomic_slice(&mut self) -> &[$atomic] {
+                const {
+                    assert!(std::mem::align_of::<$atomic>() == std::mem::align_of::<$t>());
+                }
+                let data = self.as_mut_slice();
+                // SAFETY: The atomic type has the same size and bit validity as the element type,
+                // and the same alignment on this target as checked above.
+                // The elements are borrowed uniquely for as long as the atomics are.
+                unsafe { std::slice::from_raw_parts(data.as_mut_ptr() as *const $atomic, data.len()) }
+            }
+        }
+    )*};
+}
+impl_atomic_fat!(
+    u8 => std::sync::atomic::AtomicU8,
+    u16 => std::sync::atomic::AtomicU16,
+    u32 => std::sync::atomic::AtomicU32,
+    usize => std::sync::atomic::AtomicUsize
+);
+
+impl Fat<u8, usize> {
+    /// Creates a byte fat pointer to the UTF-8 bytes of `s`.
+    // `FromStr` can't be used here, since it returns an owned value.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> &Self {
+        Self::from_slice(s.as_bytes(), s.len())
+    }
+    /// Gets the bytes as a string slice, if they are valid UTF-8.
+    pub fn as_str(&self) -> Result<&str, std::str::Utf8Error> {
+        std::str::from_utf8(self.as_slice())
+    }
+}
+
+#[cfg(feature = "bytemuck")]
+impl<T: bytemuck::Pod> Fat<T, usize> {
+    /// Reads a fat pointer that was written out as a native-endian `usize` count,
+    /// followed by the elements. This allows zero-copy access to buffers stored
+    /// on disk, such as through a memory map, which derefs to `[u8]`.
+    ///
+    /// Returns `None` if `bytes` is too short, or if the elements are not
+    /// aligned for `T`.
+    pub fn from_encoded_bytes(bytes: &[u8]) -> Option<&Self> {
+        let (header, payload) = bytes.split_first_chunk::<{ std::mem::size_of::<usize>() }>()?;
+        let count = usize::from_ne_bytes(*header);
+        let size = count.checked_mul(std::mem::size_of::<T>())?;
+        let data: &[T] = bytemuck::try_cast_slice(payload.get(..size)?).ok()?;
+        // Zero-sized elements can't be counted from a byte length.
+        (data.len() == count).then(|| Self::from_slice(data, count))
+    }
+}
+
+/// Compares the elements of a fat pointer with a slice, ignoring the metadata.
+impl<T: PartialEq, M: ElemCount> PartialEq<[T]> for Fat<T, M> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: PartialEq, M: ElemCount> PartialEq<Fat<T, M>> for [T] {
+    fn eq(&self, other: &Fat<T, M>) -> bool {
+        self == other.as_slice()
+    }
+}
+
+/// Clones the elements of a fat pointer into an array,
+/// if it has exactly `N` elements.
+impl<T: Clone, M: ElemCount, const N: usize> TryFrom<&Fat<T, M>> for [T; N] {
+    type Error = std::array::TryFromSliceError;
+    fn try_from(fat: &Fat<T, M>) -> Result<Self, Self::Error> {
+        let array: &[T; N] = fat.as_slice().try_into()?;
+        Ok(array.clone())
+    }
+}
+
+impl<T, M: SplitMeta> Fat<T, M> {
+    /// Creates a fat pointer to `data`, with metadata like `meta`.
+    fn sub<'a>(data: &'a [T], meta: &M) -> &'a Self {
+        Self::from_slice(data, meta.with_elem_count(data.len()))
+    }
+    fn sub_mut<'a>(data: &'a mut [T], meta: &M) -> &'a mut Self {
+        let meta = meta.with_elem_count(data.len());
+        Self::from_slice_mut(data, meta)
+    }
+
+    /// Divides this fat pointer into two at `mid`.
+    /// # Panics
+    /// If `mid > len()`.
+    pub fn split_at(&self, mid: usize) -> (&Self, &Self) {
+        let meta = self.meta();
+        let (a, b) = self.as_slice().split_at(mid);
+        (Self::sub(a, &meta), Self::sub(b, &meta))
+    }
+    /// Divides this fat pointer into two mutable halves at `mid`.
+    /// # Panics
+    /// If `mid > len()`.
+    pub fn split_at_mut(&mut self, mid: usize) -> (&mut Self, &mut Self) {
+        let meta = self.meta();
+        let (a, b) = self.as_mut_slice().split_at_mut(mid);
+        (Self::sub_mut(a, &meta), Self::sub_mut(b, &meta))
+    }
+
+    /// Divides this fat pointer into two at `mid`,
+    /// or at `len()` if `mid` is past the end.
+    pub fn split_at_saturating(&self, mid: usize) -> (&Self, &Self) {
+        self.split_at(mid.min(self.len()))
+    }
+
+    /// Divides this fat pointer at `approx_mid` rounded down to a multiple of `group`.
+    /// # Panics
+    /// If `group` is zero, or if the rounded split point is greater than `len()`.
+    pub fn split_at_aligned(&self, approx_mid: usize, group: usize) -> (&Self, &Self) {
+        assert!(group != 0, "`group` must not be zero");
+        self.split_at(approx_mid - approx_mid % group)
+    }
+
+    /// Divides this fat pointer into three at `a` and `b`.
+    /// # Panics
+    /// If `a > b` or `b > len()`.
+    pub fn split_at_two(&self, a: usize, b: usize) -> (&Self, &Self, &Self) {
+        assert!(a <= b, "`a` must not be greater than `b`");
+        let (head, rest) = self.split_at(b);
+        let (first, second) = head.split_at(a);
+        (first, second, rest)
+    }
+
+    /// Splits this fat pointer around the first element that matches `pred`,
+    /// returning the elements before and after it.
+    pub fn split_once(&self, pred: impl FnMut(&T) -> bool) -> Option<(&Self, &Self)> {
+        let meta = self.meta();
+        let slice = self.as_slice();
+        let i = slice.iter().position(pred)?;
+        Some((
+            Self::sub(&slice[..i], &meta),
+            Self::sub(&slice[i + 1..], &meta),
+        ))
+    }
+
+    /// Gets a fat pointer to the elements after the leading run that matches `pred`.
+    pub fn skip_while(&self, mut pred: impl FnMut(&T) -> bool) -> &Self {
+        let start = self.as_slice().iter().position(|x| !pred(x));
+        self.split_at(start.unwrap_or(self.len())).1
+    }
+
+    /// Divides this fat pointer into mutable chunks of `N` elements,
+    /// with a mutable fat pointer to the elements left over at the end.
+    /// # Panics
+    /// If `N` is zero.
+    pub fn as_chunks_mut<const N: usize>(&mut self) -> (&mut [[T; N]], &mut Self) {
+        let meta = self.meta();
+        let (chunks, rest) = self.as_mut_slice().as_chunks_mut();
+        (chunks, Self::sub_mut(rest, &meta))
+    }
+
+    /// Divides this fat pointer at `len() * numerator / denominator`.
+    /// # Panics
+    /// If `denominator` is zero, if `numerator > denominator`,
+    /// or if `len() * numerator` overflows a `usize`.
+    pub fn split_at_ratio(&self, numerator: usize, denominator: usize) -> (&Self, &Self) {
+        assert!(denominator != 0, "`denominator` must not be zero");
+        assert!(
+            numerator <= denominator,
+            "`numerator` must not be greater than `denominator`"
+        );
+        let mid = self
+            .len()
+            .checked_mul(numerator)
+            .expect("split point overflows a usize")
+            / denominator;
+        self.split_at(mid)
+    }
+
+    /// Iterates over mutable chunks of `n` elements, starting from the end.
+    /// The last chunk yielded may be shorter than `n`.
+    /// # Panics
+    /// If `n` is zero.
+    pub fn rchunks_mut(&mut self, n: usize) -> impl Iterator<Item = &mut Self> {
+        assert!(n != 0, "chunk size must not be zero");
+        let meta = self.meta();
+        self.as_mut_slice()
+            .rchunks_mut(n)
+            .map(move |chunk| Self::sub_mut(chunk, &meta))
+    }
+
+    /// Iterates over overlapping frames of `chunk` elements,
+    /// where each frame starts `chunk - overlap` elements after the last.
+    /// Elements at the end that can't fill a whole frame are skipped.
+    /// # Panics
+    /// If `overlap` is not less than `chunk`.
+    pub fn partition_overlapping(
+        &self,
+        chunk: usize,
+        overlap: usize,
+    ) -> impl Iterator<Item = &Self> {
+        assert!(overlap < chunk, "`overlap` must be less than `chunk`");
+        let meta = self.meta();
+        self.as_slice()
+            .windows(chunk)
+            .step_by(chunk - overlap)
+            .map(move |frame| Self::sub(frame, &meta))
+    }
+
+    /// Divides this fat pointer into a mutable array of the first `N` elements
+    /// and a mutable fat pointer to the rest, or `None` if there are fewer than `N`.
+    pub fn split_first_chunk_mut<const N: usize>(&mut self) -> Option<(&mut [T; N], &mut Self)> {
+        let meta = self.meta();
+        let (head, tail) = self.as_mut_slice().split_first_chunk_mut()?;
+        Some((head, Self::sub_mut(tail, &meta)))
+    }
+
+    /// Divides this fat pointer into a mutable fat pointer to the leading elements
+    /// and a mutable array of the last `N`, or `None` if there are fewer than `N`.
+    pub fn split_last_chunk_mut<const N: usize>(&mut self) -> Option<(&mut Self, &mut [T; N])> {
+        let meta = self.meta();
+        let (head, tail) = self.as_mut_slice().split_last_chunk_mut()?;
+        Some((Self::sub_mut(head, &meta), tail))
+    }
+
+    /// Gets references to the first `N` elements and a fat pointer to the rest,
+    /// or `None` if there are fewer than `N`.
+    pub fn split_array<const N: usize>(&self) -> Option<([&T; N], &Self)> {
+        let meta = self.meta();
+        let (head, tail) = self.as_slice().split_first_chunk::<N>()?;
+        Some((head.each_ref(), Self::sub(tail, &meta)))
+    }
+
+    /// Divides this fat pointer into two mutable halves at `mid`,
+    /// or returns `None` if `mid > len()`.
+    pub fn split_at_mut_checked(&mut self, mid: usize) -> Option<(&mut Self, &mut Self)> {
+        let meta = self.meta();
+        let (a, b) = self.as_mut_slice().split_at_mut_checked(mid)?;
+        Some((Self::sub_mut(a, &meta), Self::sub_mut(b, &meta)))
+    }
+
+    /// Reorders the elements so that the one at index `n` is where it would be if sorted,
+    /// with no greater elements before it and no lesser elements after it.
+    /// Returns the elements before it, the element itself, and the elements after it.
+    /// # Panics
+    /// If `n >= len()`.
+    pub fn select_nth_unstable(&mut self, n: usize) -> (&mut Self, &mut T, &mut Self)
+    where
+        T: Ord,
+    {
+        let meta = self.meta();
+        let (before, nth, after) = self.as_mut_slice().select_nth_unstable(n);
+        (
+            Self::sub_mut(before, &meta),
+            nth,
+            Self::sub_mut(after, &meta),
+        )
+    }
+
+    /// Gets a mutable fat pointer to the elements in `range`,
+    /// or `None` if the range is out of bounds.
+    pub fn get_mut_range(&mut self, range: std::ops::Range<usize>) -> Option<&mut Self> {
+        let meta = self.meta();
+        let data = self.as_mut_slice().get_mut(range)?;
+        Some(Self::sub_mut(data, &meta))
+    }
+
+    /// Iterates over disjoint mutable chunks of exactly `n` elements.
+    /// Elements at the end that can't fill a whole chunk are left out,
+    /// and can be reached with [`ChunksExactMut::into_remainder`].
+    /// # Panics
+    /// If `n` is zero.
+    pub fn chunks_exact_mut(&mut self, n: usize) -> ChunksExactMut<'_, T, M> {
+        assert!(n != 0, "chunk size must not be zero");
+        let meta = self.meta();
+        ChunksExactMut {
+            chunks: self.as_mut_slice().chunks_exact_mut(n),
+            meta,
+        }
+    }
+}
+
+/// An iterator over mutable chunks of a fat pointer, created by [`Fat::chunks_exact_mut`].
+pub struct ChunksExactMut<'a, T, M: SplitMeta> {
+    chunks: std::slice::ChunksExactMut<'a, T>,
+    meta: M,
+}
+
+impl<'a, T, M: SplitMeta + 'a> ChunksExactMut<'a, T, M> {
+    /// Gets the elements at the end that didn't fill a whole chunk.
+    pub fn into_remainder(self) -> &'a mut Fat<T, M> {
+        Fat::sub_mut(self.chunks.into_remainder(), &self.meta)
+    }
+}
+
+impl<'a, T, M: SplitMeta + 'a> Iterator for ChunksExactMut<'a, T, M> {
+    type Item = &'a mut Fat<T, M>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.chunks.next()?;
+        Some(Fat::sub_mut(chunk, &self.meta))
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<'a, T, M: SplitMeta + 'a> ExactSizeIterator for ChunksExactMut<'a, T, M> {}
+
+#[cfg(test)]
+mod tests {
+    // These also run under Miri with `-Zmiri-tree-borrows`; see the note on `Fat`.
+    use super::*;
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn non_zero_u32_round_trip() {
+        let meta = std::num::NonZeroU32::new(u32::MAX).unwrap();
+        // SAFETY: The bytes came from `into_bytes`.
+        assert_eq!(
+            unsafe { std::num::NonZeroU32::from_bytes(meta.into_bytes()) },
+            meta
+        );
+
+        let data = [7u8; 3];
+        let fat = Fat::from_slice(&data, std::num::NonZeroU32::new(3).unwrap());
+        assert_eq!(fat.meta().get(), 3);
+        assert_eq!(fat.as_slice(), &data);
+    }
+
+    #[test]
+    #[should_panic = "metadata refers to more elements than `data` holds"]
+    fn from_slice_count_too_large() {
+        let data = [1, 2, 3];
+        Fat::from_slice(&data, 4usize);
+    }
+
+    #[test]
+    #[should_panic = "metadata refers to more elements than `data` holds"]
+    fn from_slice_mut_count_too_large() {
+        let mut data = [1, 2, 3];
+        Fat::from_slice_mut(&mut data, 4usize);
+    }
+
+    #[test]
+    fn from_slice_count_smaller_takes_a_prefix() {
+        let data = [1, 2, 3];
+        assert_eq!(Fat::from_slice(&data, 2usize).as_slice(), &[1, 2]);
+        let mut data = [1, 2, 3];
+        let fat = Fat::from_slice_mut(&mut data, 2usize);
+        fat.as_mut_slice()[1] = 9;
+        assert_eq!(data, [1, 9, 3]);
+    }
+
+    #[test]
+    fn from_slice_unchecked_skips_the_check() {
+        // Metadata that doesn't count elements can hold any value,
+        // but only the unchecked constructors accept it.
+        #[derive(Clone, Copy, PartialEq, Debug)]
+        struct Tag(usize);
+        impl Meta for Tag {
+            fn into_bytes(self) -> usize {
+                self.0
+            }
+            unsafe fn from_bytes(val: usize) -> Self {
+                Self(val)
+            }
+        }
+
+        let data = [1, 2, 3];
+        // SAFETY: `Tag` does not implement `ElemCount`.
+        let fat = unsafe { Fat::from_slice_unchecked(&data, Tag(1000)) };
+        assert_eq!(fat.meta(), Tag(1000));
+        assert_eq!(fat.ptr(), data.as_ptr());
+    }
+
+    #[test]
+    fn split_once_at_separator() {
+        let data = *b"key\0v";
+        let fat = Fat::from_slice(&data, 5usize);
+        let (key, value) = fat.split_once(|&b| b == 0).unwrap();
+        assert_eq!(key.as_slice(), b"key");
+        assert_eq!(value.as_slice(), b"v");
+        assert_eq!((key.meta(), value.meta()), (3, 1));
+        assert!(key.split_once(|&b| b == 0).is_none());
+    }
+
+    #[test]
+    fn repeat_copies() {
+        let fat = Fat::from_slice(&[1, 2][..], 2usize);
+        let repeated = fat.repeat(3);
+        assert_eq!(repeated.meta(), 6);
+        assert_eq!(repeated.as_slice(), &[1, 2, 1, 2, 1, 2]);
+        assert!(fat.repeat(0).is_empty());
+    }
+
+    #[test]
+    fn iter_step_every_other() {
+        let fat = Fat::from_slice(&[0, 1, 2, 3, 4][..], 5usize);
+        assert!(fat.iter_step(2).eq(&[0, 2, 4]));
+        assert!(fat.iter_step(10).eq(&[0]));
+    }
+
+    #[test]
+    fn first_and_last_chunk() {
+        let fat = Fat::from_slice(&[1, 2, 3, 4, 5][..], 5usize);
+        assert_eq!(fat.first_chunk::<2>(), Some(&[1, 2]));
+        assert_eq!(fat.last_chunk::<2>(), Some(&[4, 5]));
+        assert_eq!(fat.first_chunk::<6>(), None);
+        assert_eq!(fat.last_chunk::<6>(), None);
+    }
+
+    #[test]
+    fn partition_evens_and_odds() {
+        let fat = Fat::from_slice(&[1, 2, 3, 4][..], 4usize);
+        let (evens, odds) = fat.partition_to_boxes(|x| x % 2 == 0);
+        assert_eq!(evens.as_slice(), &[2, 4]);
+        assert_eq!(odds.as_slice(), &[1, 3]);
+        assert_eq!((evens.meta(), odds.meta()), (2, 2));
+    }
+
+    #[test]
+    fn shift_left_fills_the_end() {
+        let mut data = [1, 2, 3, 4];
+        Fat::from_slice_mut(&mut data, 4usize).shift_left(2, 0);
+        assert_eq!(data, [3, 4, 0, 0]);
+        Fat::from_slice_mut(&mut data, 4usize).shift_left(10, 9);
+        assert_eq!(data, [9, 9, 9, 9]);
+    }
+
+    #[test]
+    fn checksum_matches_contents() {
+        use std::collections::hash_map::DefaultHasher;
+        let a = [1, 2, 3];
+        let b = [1, 2, 3];
+        let c = [1, 2, 4];
+        let sum = |data: &[i32]| Fat::from_slice(data, data.len()).checksum::<DefaultHasher>();
+        assert_eq!(sum(&a), sum(&b));
+        assert_ne!(sum(&a), sum(&c));
+        assert_ne!(sum(&a), sum(&a[..2]));
+    }
+
+    #[test]
+    fn as_chunks_mut_writes_each_chunk() {
+        let mut data = [0u8; 7];
+        let fat = Fat::from_slice_mut(&mut data, 7usize);
+        let (chunks, rest) = fat.as_chunks_mut::<2>();
+        assert_eq!(chunks.len(), 3);
+        for (i, chunk) in chunks.iter_mut().enumerate() {
+            *chunk = [i as u8; 2];
+        }
+        assert_eq!(rest.meta(), 1);
+        rest.as_mut_slice()[0] = 9;
+        assert_eq!(data, [0, 0, 1, 1, 2, 2, 9]);
+    }
+
+    #[test]
+    fn array_ref_at_offset() {
+        let data: Vec<i32> = (0..8).collect();
+        let fat = Fat::from_slice(&data, 8usize);
+        assert_eq!(fat.array_ref_at::<3>(2), Some(&[2, 3, 4]));
+        assert_eq!(fat.array_ref_at::<3>(5), Some(&[5, 6, 7]));
+        assert_eq!(fat.array_ref_at::<3>(6), None);
+        assert_eq!(fat.array_ref_at::<3>(usize::MAX), None);
+    }
+
+    #[test]
+    fn split_at_ratio_sizes() {
+        let data = [0u8; 10];
+        let (train, test) = Fat::from_slice(&data, 10usize).split_at_ratio(7, 10);
+        assert_eq!((train.len(), test.len()), (7, 3));
+    }
+
+    #[test]
+    fn approx_eq_floats() {
+        let a = [1.0f32, 2.0, 3.0];
+        let b = [1.0f32, 2.001, 2.999];
+        let fa = Fat::from_slice(&a, 3usize);
+        assert!(fa.approx_eq(Fat::from_slice(&b, 3usize), 0.01));
+        assert!(!fa.approx_eq(Fat::from_slice(&b, 3usize), 0.0001));
+        assert!(!fa.approx_eq(Fat::from_slice(&b[..2], 2usize), 0.01));
+    }
+
+    #[test]
+    fn count_saturating_caps() {
+        let data = [0u8; 10];
+        let fat = Fat::from_slice(&data, 10usize);
+        assert_eq!(fat.count_saturating(4), 4);
+        assert_eq!(fat.count_saturating(100), 10);
+    }
+
+    #[test]
+    fn rchunks_mut_from_the_end() {
+        let mut data = [0u8; 5];
+        let fat = Fat::from_slice_mut(&mut data, 5usize);
+        let mut lens = Vec::new();
+        for (i, chunk) in fat.rchunks_mut(2).enumerate() {
+            lens.push(chunk.meta());
+            chunk.as_mut_slice().fill(i as u8 + 1);
+        }
+        assert_eq!(lens, [2, 2, 1]);
+        assert_eq!(data, [3, 2, 2, 1, 1]);
+    }
+
+    #[test]
+    fn sort_strings_by_cached_length() {
+        let mut data = ["ccc", "a", "bb"].map(String::from);
+        Fat::from_slice_mut(&mut data, 3usize).sort_by_cached_key(String::len);
+        assert_eq!(data, ["a", "bb", "ccc"]);
+    }
+
+    #[test]
+    fn unit_slice_holds_packed_metadata() {
+        let data = [0u16; 4];
+        let fat = Fat::from_slice(&data, 3usize);
+        assert_eq!(fat.as_unit_slice().len(), fat.meta().into_bytes());
+        assert_eq!(fat.as_unit_slice().len(), 3);
+    }
+
+    #[test]
+    fn split_first_chunk_mut_writes_both_parts() {
+        let mut data = [0u8; 5];
+        let fat = Fat::from_slice_mut(&mut data, 5usize);
+        let (head, tail) = fat.split_first_chunk_mut::<2>().unwrap();
+        *head = [1, 2];
+        assert_eq!(tail.meta(), 3);
+        tail.as_mut_slice().fill(3);
+        assert_eq!(data, [1, 2, 3, 3, 3]);
+
+        let mut short = [0u8; 1];
+        assert!(Fat::from_slice_mut(&mut short, 1usize)
+            .split_first_chunk_mut::<2>()
+            .is_none());
+    }
+
+    #[test]
+    fn max_and_min_by_partial_cmp() {
+        let data = [2.5f64, -1.0, 7.25, 0.0];
+        let fat = Fat::from_slice(&data, 4usize);
+        assert_eq!(fat.max_by(|a, b| a.partial_cmp(b).unwrap()), Some(&7.25));
+        assert_eq!(fat.min_by(|a, b| a.partial_cmp(b).unwrap()), Some(&-1.0));
+        let empty = Fat::<f64, usize>::from_slice(&[], 0);
+        assert_eq!(empty.max_by(|a, b| a.partial_cmp(b).unwrap()), None);
+    }
+
+    #[test]
+    fn str_round_trip() {
+        let fat = Fat::from_str("hello");
+        assert_eq!(fat.meta(), 5);
+        assert_eq!(fat.as_slice(), b"hello");
+        assert_eq!(fat.as_str(), Ok("hello"));
+        assert!(Fat::from_slice(&[0xff][..], 1usize).as_str().is_err());
+    }
+
+    #[test]
+    fn split_array_of_strings() {
+        let data = ["a", "b", "c"].map(String::from);
+        let fat = Fat::from_slice(&data, 3usize);
+        let ([a, b], rest) = fat.split_array::<2>().unwrap();
+        assert_eq!((a.as_str(), b.as_str()), ("a", "b"));
+        assert_eq!(rest.as_slice(), &["c"]);
+        assert!(fat.split_array::<4>().is_none());
+    }
+
+    #[test]
+    fn split_at_mut_checked_bounds() {
+        let mut data = [0u8; 4];
+        let fat = Fat::from_slice_mut(&mut data, 4usize);
+        assert!(fat.split_at_mut_checked(5).is_none());
+        let (a, b) = fat.split_at_mut_checked(1).unwrap();
+        a.as_mut_slice()[0] = 1;
+        b.as_mut_slice().fill(2);
+        assert_eq!((a.meta(), b.meta()), (1, 3));
+        assert_eq!(data, [1, 2, 2, 2]);
+    }
+
+    #[test]
+    fn prefix_sums_scan() {
+        let sums = Fat::from_slice(&[1, 2, 3][..], 3usize).prefix_sums();
+        assert_eq!(sums.as_slice(), &[1, 3, 6]);
+        assert!(Fat::<i32, usize>::from_slice(&[], 0)
+            .prefix_sums()
+            .is_empty());
+    }
+
+    #[test]
+    fn argsort() {
+        let data = [30, 10, 20];
+        let fat = Fat::from_slice(&data, 3usize);
+        assert_eq!(fat.sort_indices(), [1, 2, 0]);
+        assert_eq!(data, [30, 10, 20]);
+    }
+
+    #[test]
+    fn min_max_single_pass() {
+        let fat = Fat::from_slice(&[3, 1, 4, 1, 5, 9, 2][..], 7usize);
+        assert_eq!(fat.min_max(), Some((&1, &9)));
+        assert_eq!(Fat::<i32, usize>::from_slice(&[], 0).min_max(), None);
+    }
+
+    #[test]
+    fn partition_in_place_evens_first() {
+        let mut data = [1, 2, 3, 4, 5];
+        let split = Fat::from_slice_mut(&mut data, 5usize).partition_in_place(|x| x % 2 == 0);
+        assert_eq!(split, 2);
+        assert!(data[..split].iter().all(|x| x % 2 == 0));
+        assert!(data[split..].iter().all(|x| x % 2 == 1));
+    }
+
+    #[test]
+    fn select_median() {
+        let mut data = [9, 1, 8, 2, 7];
+        let fat = Fat::from_slice_mut(&mut data, 5usize);
+        let (less, median, greater) = fat.select_nth_unstable(2);
+        assert_eq!(*median, 7);
+        assert!(less.as_slice().iter().all(|&x| x <= 7));
+        assert!(greater.as_slice().iter().all(|&x| x >= 7));
+        assert_eq!((less.meta(), greater.meta()), (2, 2));
+    }
+
+    #[test]
+    fn moving_average_window_3() {
+        let data = [1.0f64, 2.0, 3.0, 4.0, 5.0];
+        let averages = Fat::from_slice(&data, 5usize).moving_average(3);
+        let expected = [2.0, 3.0, 4.0];
+        assert!(averages.approx_eq(Fat::from_slice(&expected, 3usize), 1e-12));
+    }
+
+    #[test]
+    fn get_mut_range_sub_view() {
+        let mut data = [0u8; 4];
+        let fat = Fat::from_slice_mut(&mut data, 4usize);
+        assert!(fat.get_mut_range(3..5).is_none());
+        let middle = fat.get_mut_range(1..3).unwrap();
+        assert_eq!(middle.meta(), 2);
+        middle.as_mut_slice().fill(7);
+        assert_eq!(data, [0, 7, 7, 0]);
+    }
+
+    #[test]
+    fn chunks_exact_mut_and_remainder() {
+        let mut data = [0u8; 7];
+        let fat = Fat::from_slice_mut(&mut data, 7usize);
+        let mut chunks = fat.chunks_exact_mut(3);
+        assert_eq!(chunks.len(), 2);
+        for (i, chunk) in chunks.by_ref().enumerate() {
+            assert_eq!(chunk.meta(), 3);
+            chunk.as_mut_slice().fill(i as u8 + 1);
+        }
+        let rest = chunks.into_remainder();
+        assert_eq!(rest.meta(), 1);
+        rest.as_mut_slice()[0] = 9;
+        assert_eq!(data, [1, 1, 1, 2, 2, 2, 9]);
+    }
+
+    #[test]
+    fn partition_overlapping_frames() {
+        let data: Vec<i32> = (0..10).collect();
+        let fat = Fat::from_slice(&data, 10usize);
+        let frames: Vec<_> = fat.partition_overlapping(4, 1).collect();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].as_slice(), &[0, 1, 2, 3]);
+        assert_eq!(frames[1].as_slice(), &[3, 4, 5, 6]);
+        assert_eq!(frames[2].as_slice(), &[6, 7, 8, 9]);
+        assert!(frames.iter().all(|f| f.meta() == 4));
+
+        // The trailing element can't fill a frame, so it is skipped.
+        let data: Vec<i32> = (0..11).collect();
+        let fat = Fat::from_slice(&data, 11usize);
+        let last = fat.partition_overlapping(4, 1).last().unwrap();
+        assert_eq!(last.as_slice(), &[6, 7, 8, 9]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn partition_overlapping_too_much_overlap() {
+        let data = [0u8; 10];
+        let _ = Fat::from_slice(&data, 10usize).partition_overlapping(4, 4);
+    }
+
+    #[test]
+    fn reading_from_the_end() {
+        let data = ['a', 'b', 'c', 'd'];
+        let fat = Fat::from_slice(&data, 4usize);
+        assert_eq!(fat.get_from_end(0), Some(&'d'));
+        assert_eq!(fat.get_from_end(1), Some(&'c'));
+        assert_eq!(fat.get_from_end(4), None);
+        assert_eq!(fat.get_from_end(usize::MAX), None);
+        assert_eq!(fat.iter_rev().collect::<String>(), "dcba");
+    }
+
+    #[test]
+    fn split_last_chunk_mut_writes_both_parts() {
+        let mut data = [0u8; 5];
+        let fat = Fat::from_slice_mut(&mut data, 5usize);
+        let (head, tail) = fat.split_last_chunk_mut::<2>().unwrap();
+        assert_eq!(head.meta(), 3);
+        head.as_mut_slice().fill(1);
+        *tail = [2, 3];
+        assert_eq!(data, [1, 1, 1, 2, 3]);
+
+        let mut short = [0u8; 1];
+        assert!(Fat::from_slice_mut(&mut short, 1usize)
+            .split_last_chunk_mut::<2>()
+            .is_none());
+    }
+
+    #[test]
+    fn compare_with_slices() {
+        let data = [1, 2, 3];
+        let fat = Fat::from_slice(&data, 3usize);
+        assert!(fat == &[1, 2, 3][..]);
+        assert!(&[1, 2, 3][..] == fat);
+        assert!(fat != &[1, 2][..]);
+    }
+
+    #[test]
+    fn split_at_two_points() {
+        let data: Vec<i32> = (0..6).collect();
+        let (a, b, c) = Fat::from_slice(&data, 6usize).split_at_two(2, 4);
+        assert_eq!(a.as_slice(), &[0, 1]);
+        assert_eq!(b.as_slice(), &[2, 3]);
+        assert_eq!(c.as_slice(), &[4, 5]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_at_two_out_of_order() {
+        let data = [0u8; 6];
+        Fat::from_slice(&data, 6usize).split_at_two(4, 2);
+    }
+
+    #[test]
+    fn add_index_to_each_element() {
+        let mut data = [5usize; 3];
+        for (i, x) in Fat::from_slice_mut(&mut data, 3usize).iter_mut_indexed() {
+            *x += i;
+        }
+        assert_eq!(data, [5, 6, 7]);
+    }
+
+    #[test]
+    fn clone_range() {
+        let data: Vec<i32> = (0..5).collect();
+        let boxed = Fat::from_slice(&data, 5usize).clone_range_to_box(1..4);
+        assert_eq!(boxed.meta(), 3);
+        assert_eq!(boxed.as_slice(), &[1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn clone_range_out_of_bounds() {
+        let data = [0u8; 5];
+        Fat::from_slice(&data, 5usize).clone_range_to_box(3..6);
+    }
+
+    #[test]
+    fn data_eq_ignores_metadata() {
+        let data = [1, 2, 3];
+        let a = Fat::from_slice(&data, 3usize);
+        let b = Fat::from_slice(&data, std::num::NonZeroU32::new(3).unwrap());
+        assert!(a.data_eq(b));
+        assert!(!a.data_eq(Fat::from_slice(&data[..2], 2usize)));
+    }
+
+    #[test]
+    fn take_and_skip_while_even() {
+        let data = [2, 4, 6, 1, 8];
+        let fat = Fat::from_slice(&data, 5usize);
+        let even = |x: &i32| x % 2 == 0;
+        assert_eq!(fat.take_while_to_box(even).as_slice(), &[2, 4, 6]);
+        assert_eq!(fat.skip_while(even).as_slice(), &[1, 8]);
+        assert_eq!(fat.skip_while(|_| true).len(), 0);
+    }
+
+    #[test]
+    fn split_at_saturating_past_the_end() {
+        let data = [1, 2, 3];
+        let fat = Fat::from_slice(&data, 3usize);
+        let (head, tail) = fat.split_at_saturating(10);
+        assert_eq!(head.as_slice(), &[1, 2, 3]);
+        assert_eq!(tail.len(), 0);
+        let (head, tail) = fat.split_at_saturating(1);
+        assert_eq!((head.len(), tail.len()), (1, 2));
+    }
+
+    #[test]
+    fn weighted_sum_of_squares() {
+        let data = [1, 2, 3];
+        assert_eq!(Fat::from_slice(&data, 3usize).weighted_sum(|x| x * x), 14);
+    }
+
+    #[test]
+    fn split_at_aligned_rounds_down() {
+        let data = [0u8; 10];
+        let (head, tail) = Fat::from_slice(&data, 10usize).split_at_aligned(7, 4);
+        assert_eq!((head.len(), tail.len()), (4, 6));
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_at_aligned_zero_group() {
+        let data = [0u8; 10];
+        Fat::from_slice(&data, 10usize).split_at_aligned(7, 0);
+    }
+
+    #[test]
+    fn position_of_min_and_max() {
+        let data = [3, 1, 4, 1, 5];
+        let fat = Fat::from_slice(&data, 5usize);
+        assert_eq!(fat.position_min(), Some(1));
+        assert_eq!(fat.position_max(), Some(4));
+        let empty: [i32; 0] = [];
+        assert_eq!(Fat::from_slice(&empty, 0usize).position_min(), None);
+    }
+
+    #[test]
+    fn position_max_first_of_ties() {
+        let data = [2, 5, 1, 5, 5];
+        let fat = Fat::from_slice(&data, 5usize);
+        assert_eq!(fat.position_max(), Some(1));
+        assert_eq!(Fat::from_slice(&[1, 1, 1], 3usize).position_min(), Some(0));
+    }
+
+    #[test]
+    fn unique_keeps_first_occurrences() {
+        let data = [1, 2, 1, 3, 2];
+        let unique = Fat::from_slice(&data, 5usize).unique_to_box();
+        assert_eq!(unique.as_slice(), &[1, 2, 3]);
+        assert_eq!(unique.meta(), 3);
+    }
+
+    #[test]
+    fn try_for_each_stops_at_error() {
+        let data = [1, 2, -3, 4];
+        let mut seen = Vec::new();
+        let result = Fat::from_slice(&data, 4usize).try_for_each(|&x| {
+            if x < 0 {
+                return Err(x);
+            }
+            seen.push(x);
+            Ok(())
+        });
+        assert_eq!(result, Err(-3));
+        assert_eq!(seen, [1, 2]);
+    }
+
+    #[test]
+    fn try_from_into_array() {
+        let data = [1, 2, 3];
+        let fat = Fat::from_slice(&data, 3usize);
+        assert_eq!(<[i32; 3]>::try_from(fat).ok(), Some([1, 2, 3]));
+        assert!(<[i32; 4]>::try_from(fat).is_err());
+    }
+
+    #[test]
+    fn copied_elements_sum() {
+        let data = [1u64, 2, 3, 4];
+        let fat = Fat::from_slice(&data, 4usize);
+        assert_eq!(fat.copied_elements().sum::<u64>(), 10);
+    }
+
+    #[test]
+    fn binary_search_by_first_field() {
+        let data = [(1, 'a'), (3, 'b'), (5, 'c')];
+        let fat = Fat::from_slice(&data, 3usize);
+        assert_eq!(fat.binary_search_by_key(&3, |&(k, _)| k), Ok(1));
+        assert_eq!(fat.binary_search_by_key(&4, |&(k, _)| k), Err(2));
+    }
+
+    #[test]
+    fn iter_eq_checks_length() {
+        let data = [0, 1, 2];
+        let fat = Fat::from_slice(&data, 3usize);
+        assert!(fat.iter_eq(0..3));
+        assert!(!fat.iter_eq(0..2));
+        assert!(!fat.iter_eq(0..4));
+        assert!(!fat.iter_eq([0, 1, 3]));
+    }
+
+    #[test]
+    fn atomic_increments_from_threads() {
+        use std::sync::atomic::Ordering;
+        let mut data = [0u32; 4];
+        let atomics = Fat::from_slice_mut(&mut data, 4usize).as_atomic_slice();
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        for a in atomics {
+                            a.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+        assert_eq!(data, [400; 4]);
+    }
+
+    #[test]
+    #[cfg(feature = "bytemuck")]
+    fn from_encoded_bytes_checks_length_and_alignment() {
+        // Backed by `u64`s, so that the payload after the header is aligned for `u32`.
+        let mut buf = [0u64; 4];
+        let bytes: &mut [u8] = bytemuck::cast_slice_mut(&mut buf);
+        let header = std::mem::size_of::<usize>();
+        bytes[..header].copy_from_slice(&3usize.to_ne_bytes());
+        for (i, x) in [7u32, 8, 9].iter().enumerate() {
+            bytes[header + 4 * i..][..4].copy_from_slice(&x.to_ne_bytes());
+        }
+
+        let fat = Fat::<u32, usize>::from_encoded_bytes(bytes).unwrap();
+        assert_eq!(fat.meta(), 3);
+        assert_eq!(fat.as_slice(), &[7, 8, 9]);
+
+        // Too short for the header, or for the elements.
+        assert!(Fat::<u32, usize>::from_encoded_bytes(&bytes[..header - 1]).is_none());
+        assert!(Fat::<u32, usize>::from_encoded_bytes(&bytes[..header + 11]).is_none());
+
+        // The payload is misaligned when the header starts one byte in.
+        let mut shifted = [0u64; 4];
+        let shifted: &mut [u8] = bytemuck::cast_slice_mut(&mut shifted);
+        shifted[1..][..header + 12].copy_from_slice(&bytes[..header + 12]);
+        assert!(Fat::<u32, usize>::from_encoded_bytes(&shifted[1..]).is_none());
+
+        // Zero-sized elements can't be read, whatever the count.
+        assert!(Fat::<(), usize>::from_encoded_bytes(bytes).is_none());
     }
 }
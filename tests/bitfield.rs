@@ -0,0 +1,7 @@
+#![cfg(all(target_pointer_width = "64", not(miri)))]
+
+#[test]
+fn bitfield_meta() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/bitfield_too_wide.rs");
+}
@@ -0,0 +1,10 @@
+#![cfg(all(feature = "derive", target_pointer_width = "64", not(miri)))]
+
+#[test]
+fn derive_meta() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive_pass.rs");
+    t.pass("tests/ui/derive_repr_args.rs");
+    t.compile_fail("tests/ui/derive_too_small.rs");
+    t.compile_fail("tests/ui/derive_padding.rs");
+}
@@ -0,0 +1,8 @@
+fat_ptr::bitfield_meta! {
+    struct TooWide {
+        low: 40,
+        high: 40,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use fat_ptr::Meta;
+
+// The fields add up to a usize, but the tuple has padding inside it.
+#[derive(Clone, Copy, Meta)]
+#[repr(transparent)]
+struct Padded((u8, u32, u16));
+
+fn main() {}
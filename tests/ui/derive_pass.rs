@@ -0,0 +1,19 @@
+use fat_ptr::{Fat, Meta};
+
+#[derive(Clone, Copy, PartialEq, Debug, Meta)]
+#[repr(C)]
+struct Dim {
+    rows: u32,
+    cols: u32,
+}
+
+fn main() {
+    let dim = Dim { rows: 2, cols: 3 };
+    // SAFETY: The bytes came from `into_bytes`.
+    assert_eq!(unsafe { Dim::from_bytes(dim.into_bytes()) }, dim);
+
+    let data = [0u8; 6];
+    // SAFETY: `Dim` does not implement `ElemCount`.
+    let fat = unsafe { Fat::from_slice_unchecked(&data, dim) };
+    assert_eq!(fat.meta(), dim);
+}
@@ -0,0 +1,26 @@
+use fat_ptr::Meta;
+
+#[derive(Clone, Copy, PartialEq, Debug, Meta)]
+#[repr(C, align(8))]
+struct Aligned {
+    rows: u32,
+    cols: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Meta)]
+#[repr(C, packed(2))]
+struct Packed {
+    tag: u16,
+    rest: u16,
+    count: u32,
+}
+
+fn main() {
+    let aligned = Aligned { rows: 2, cols: 3 };
+    // SAFETY: The bytes came from `into_bytes`.
+    assert_eq!(unsafe { Aligned::from_bytes(aligned.into_bytes()) }, aligned);
+
+    let packed = Packed { tag: 1, rest: 2, count: 3 };
+    // SAFETY: The bytes came from `into_bytes`.
+    assert_eq!(unsafe { Packed::from_bytes(packed.into_bytes()) }, packed);
+}
@@ -0,0 +1,10 @@
+use fat_ptr::Meta;
+
+#[derive(Clone, Copy, Meta)]
+#[repr(C)]
+struct TooSmall {
+    rows: u16,
+    cols: u16,
+}
+
+fn main() {}
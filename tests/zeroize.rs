@@ -0,0 +1,40 @@
+#![cfg(feature = "zeroize")]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use fat_ptr::{FatBox, ZeroizeOnDrop};
+
+/// The address of the allocation to inspect when it is freed.
+static WATCHED: AtomicUsize = AtomicUsize::new(0);
+/// Whether the watched allocation was all zeroes when it was freed.
+static WAS_ZEROED: AtomicBool = AtomicBool::new(false);
+
+/// Checks the contents of the watched allocation before freeing it.
+struct Inspecting;
+
+unsafe impl GlobalAlloc for Inspecting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if ptr as usize == WATCHED.load(Ordering::SeqCst) {
+            let bytes = std::slice::from_raw_parts(ptr, layout.size());
+            WAS_ZEROED.store(bytes.iter().all(|&b| b == 0), Ordering::SeqCst);
+        }
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Inspecting = Inspecting;
+
+#[test]
+fn zeroes_before_freeing() {
+    let secret = ZeroizeOnDrop::new(FatBox::from_vec(vec![0xAAu8; 32], 32usize));
+    assert!(secret.as_slice().iter().all(|&b| b == 0xAA));
+    WATCHED.store(secret.ptr() as usize, Ordering::SeqCst);
+    drop(secret);
+    WATCHED.store(0, Ordering::SeqCst);
+    assert!(WAS_ZEROED.load(Ordering::SeqCst));
+}